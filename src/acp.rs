@@ -0,0 +1,799 @@
+//! ACP proxy mode (`symposium acp -- <agent command>`).
+//!
+//! Sits between an editor and an ACP agent, forwarding JSON-RPC messages in
+//! both directions while adding Symposium to the conversation:
+//!
+//! - `session/new` and `session/load` get a stdio MCP server entry that runs
+//!   `symposium mcp`, so the agent gets the `rust`, `crate`, and `cargo` tools.
+//! - The first `session/prompt` of each session is prefixed with the skills
+//!   available for the session's workspace.
+//! - `tool_call` session updates from the agent run the matching plugin
+//!   `PreToolUse` hooks, with the same payload shape as `symposium hook`.
+//!   Their output is logged, never written to the editor.
+//! - `session/request_permission` requests are answered by the proxy when
+//!   the hooks for that tool call decided: a `deny` (or exit status 2)
+//!   rejects the call and an `allow` approves it, without asking the editor.
+//!   The request is held back while the hooks run, but the agent's other
+//!   messages are not; if the hooks leave it to the editor, it is passed on.
+//!
+//! The agent executes its tools itself, so hooks can only block tool calls
+//! the agent asks permission for; for other calls they are observers.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use futures::FutureExt;
+use futures::future::{BoxFuture, Shared};
+use serde_json::{Map, Value, json};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::hook::{HookOutput, HookPayload, HookSubPayload, PermissionDecision, PreToolUsePayload};
+
+/// Name of the MCP server entry added to new sessions.
+const MCP_SERVER_NAME: &str = "symposium";
+
+/// Runs the plugin hooks for a tool call reported by the agent.
+pub type HookRunner = Arc<dyn Fn(HookPayload) -> BoxFuture<'static, Vec<HookOutput>> + Send + Sync>;
+
+/// The pending or finished hook decision for a tool call.
+type PendingDecision = Shared<BoxFuture<'static, Option<PermissionDecision>>>;
+
+/// Produces the skill context injected into a session's first prompt.
+pub type ContextProvider = Arc<dyn Fn(PathBuf) -> BoxFuture<'static, Option<String>> + Send + Sync>;
+
+/// What the proxy adds to the conversation.
+#[derive(Clone)]
+pub struct ProxyOptions {
+    /// Executable launched by the agent as the Symposium MCP server.
+    pub symposium_exe: PathBuf,
+    pub hooks: HookRunner,
+    pub context: ContextProvider,
+}
+
+impl ProxyOptions {
+    /// Options that run the installed plugins' hooks and skills.
+    fn installed() -> Result<Self> {
+        let symposium_exe =
+            std::env::current_exe().context("failed to locate the symposium executable")?;
+        Ok(Self {
+            symposium_exe,
+            hooks: Arc::new(|payload| {
+                let handle = tokio::runtime::Handle::current();
+                // Hooks run as blocking child processes; `dispatch_hook`
+                // captures and logs their stdout, which here is the editor's
+                // protocol stream.
+                tokio::task::spawn_blocking(move || {
+                    handle.block_on(crate::hook::dispatch_hook(payload))
+                })
+                .map(Result::unwrap_or_default)
+                .boxed()
+            }),
+            context: Arc::new(|cwd| Box::pin(skill_context(cwd))),
+        })
+    }
+}
+
+/// Spawn the agent and proxy between it and the editor on stdin/stdout.
+pub async fn run(agent: &[String]) -> Result<()> {
+    let Some((program, args)) = agent.split_first() else {
+        bail!("usage: symposium acp -- <agent command> [args...]");
+    };
+
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to start agent `{program}`"))?;
+
+    let agent_in = child.stdin.take().context("agent stdin unavailable")?;
+    let agent_out = child.stdout.take().context("agent stdout unavailable")?;
+
+    proxy(
+        BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+        agent_in,
+        BufReader::new(agent_out),
+        ProxyOptions::installed()?,
+    )
+    .await?;
+
+    let status = child.wait().await.context("failed waiting for agent")?;
+    tracing::info!(?status, "agent exited");
+    Ok(())
+}
+
+/// Proxy newline-delimited JSON-RPC between an editor and an agent.
+///
+/// Returns when the agent closes its output. When the editor closes its
+/// output first, the agent's input is closed and its remaining output is
+/// still forwarded.
+pub async fn proxy(
+    editor_in: impl AsyncBufRead + Unpin,
+    editor_out: impl AsyncWrite + Unpin,
+    agent_in: impl AsyncWrite + Unpin,
+    agent_out: impl AsyncBufRead + Unpin,
+    options: ProxyOptions,
+) -> Result<()> {
+    let (replies, replies_rx) = mpsc::unbounded_channel();
+    let (forwarded, forwarded_rx) = mpsc::unbounded_channel();
+    let state = Arc::new(ProxyState::new(options, replies, forwarded));
+
+    let to_agent = pump(editor_in, agent_in, replies_rx, |message| {
+        let state = state.clone();
+        async move { Some(state.handle_editor_message(message).await) }
+    });
+    // Nothing is sent to the editor except what the agent sends, some of it
+    // (permission requests the hooks left to the editor) after a delay.
+    let to_editor = pump(agent_out, editor_out, forwarded_rx, |message| {
+        let state = state.clone();
+        async move { state.handle_agent_message(message) }
+    });
+    tokio::pin!(to_agent, to_editor);
+
+    tokio::select! {
+        result = &mut to_editor => result,
+        result = &mut to_agent => {
+            result?;
+            to_editor.await
+        }
+    }
+}
+
+/// Copy lines from `input` to `output`, passing each JSON message through
+/// `transform`, which may drop it by returning `None`. Lines that aren't JSON
+/// objects are forwarded untouched. Messages from `injected` are written to
+/// `output` as well.
+async fn pump<F, Fut>(
+    input: impl AsyncBufRead + Unpin,
+    mut output: impl AsyncWrite + Unpin,
+    mut injected: mpsc::UnboundedReceiver<Map<String, Value>>,
+    transform: F,
+) -> Result<()>
+where
+    F: Fn(Map<String, Value>) -> Fut,
+    Fut: Future<Output = Option<Map<String, Value>>>,
+{
+    let mut lines = input.lines();
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    break;
+                };
+                match serde_json::from_str::<Value>(&line) {
+                    Ok(Value::Object(message)) => match transform(message).await {
+                        Some(message) => serde_json::to_string(&message)?,
+                        None => continue,
+                    },
+                    _ => line,
+                }
+            }
+            Some(message) = injected.recv() => serde_json::to_string(&message)?,
+        };
+        output.write_all(line.as_bytes()).await?;
+        output.write_all(b"\n").await?;
+        output.flush().await?;
+    }
+    output.shutdown().await?;
+    Ok(())
+}
+
+struct ProxyState {
+    options: ProxyOptions,
+    sessions: Mutex<Sessions>,
+    /// Hook decisions for tool calls still in progress, by [`tool_call_key`].
+    decisions: Mutex<HashMap<String, PendingDecision>>,
+    /// Messages the proxy itself sends to the agent.
+    replies: mpsc::UnboundedSender<Map<String, Value>>,
+    /// Agent messages held back and then passed on to the editor.
+    forwarded: mpsc::UnboundedSender<Map<String, Value>>,
+}
+
+#[derive(Default)]
+struct Sessions {
+    /// Working directories of `session/new` requests awaiting a response, by request id.
+    pending: HashMap<String, PathBuf>,
+    /// Working directory of each known session.
+    cwd: HashMap<String, PathBuf>,
+    /// Sessions whose first prompt has already been given skill context.
+    prompted: HashSet<String>,
+}
+
+impl ProxyState {
+    fn new(
+        options: ProxyOptions,
+        replies: mpsc::UnboundedSender<Map<String, Value>>,
+        forwarded: mpsc::UnboundedSender<Map<String, Value>>,
+    ) -> Self {
+        Self {
+            options,
+            sessions: Mutex::default(),
+            decisions: Mutex::default(),
+            replies,
+            forwarded,
+        }
+    }
+
+    async fn handle_editor_message(&self, mut message: Map<String, Value>) -> Map<String, Value> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        match method {
+            "session/new" | "session/load" => {
+                let id = message.get("id").map(Value::to_string);
+                if let Some(params) = message.get_mut("params").and_then(Value::as_object_mut) {
+                    self.add_mcp_server(params);
+                    let cwd = params.get("cwd").and_then(Value::as_str).map(PathBuf::from);
+                    let session_id = params.get("sessionId").and_then(Value::as_str);
+                    let mut sessions = self.sessions.lock().unwrap();
+                    match (cwd, session_id, id) {
+                        (Some(cwd), Some(session_id), _) => {
+                            sessions.cwd.insert(session_id.to_string(), cwd);
+                        }
+                        (Some(cwd), None, Some(id)) => {
+                            sessions.pending.insert(id, cwd);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "session/prompt" => {
+                if let Some(params) = message.get_mut("params").and_then(Value::as_object_mut) {
+                    self.add_context(params).await;
+                }
+            }
+            _ => {}
+        }
+        message
+    }
+
+    fn handle_agent_message(
+        self: &Arc<Self>,
+        message: Map<String, Value>,
+    ) -> Option<Map<String, Value>> {
+        let method = message.get("method").and_then(Value::as_str);
+        if let Some(id) = message.get("id").map(Value::to_string)
+            && method.is_none()
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            if let Some(cwd) = sessions.pending.remove(&id)
+                && let Some(session_id) = message
+                    .get("result")
+                    .and_then(|result| result.get("sessionId"))
+                    .and_then(Value::as_str)
+            {
+                sessions.cwd.insert(session_id.to_string(), cwd);
+            }
+        } else if method == Some("session/update")
+            && let Some(params) = message.get("params")
+        {
+            self.observe_tool_call(params);
+        } else if method == Some("session/request_permission") {
+            // The hooks may still be running; wait for them without holding
+            // up the agent's other messages to the editor.
+            let state = self.clone();
+            tokio::spawn(async move {
+                match state.answer_permission(&message).await {
+                    Some(reply) => {
+                        let _ = state.replies.send(reply);
+                    }
+                    None => {
+                        let _ = state.forwarded.send(message);
+                    }
+                }
+            });
+            return None;
+        }
+        Some(message)
+    }
+
+    /// Start the hooks for a new tool call, and forget the decisions of
+    /// finished ones.
+    fn observe_tool_call(&self, params: &Value) {
+        let Some(update) = params.get("update") else {
+            return;
+        };
+        let Some(session_id) = params.get("sessionId").and_then(Value::as_str) else {
+            return;
+        };
+        let Some(key) = tool_call_key(session_id, update) else {
+            return;
+        };
+        match update.get("sessionUpdate").and_then(Value::as_str) {
+            Some("tool_call") => {
+                let decision = (self.options.hooks)(self.hook_payload(session_id, update))
+                    .map(|outputs| crate::hook::permission_decision(&outputs))
+                    .boxed()
+                    .shared();
+                // Run the hooks now, whether or not permission is requested.
+                tokio::spawn(decision.clone());
+                self.decisions.lock().unwrap().insert(key, decision);
+            }
+            Some("tool_call_update")
+                if matches!(
+                    update.get("status").and_then(Value::as_str),
+                    Some("completed" | "failed")
+                ) =>
+            {
+                self.decisions.lock().unwrap().remove(&key);
+            }
+            _ => {}
+        }
+    }
+
+    /// Answer a `session/request_permission` request from the hooks'
+    /// decision for the tool call, or `None` to leave it to the editor.
+    async fn answer_permission(&self, message: &Map<String, Value>) -> Option<Map<String, Value>> {
+        let id = message.get("id")?;
+        let params = message.get("params")?;
+        let session_id = params.get("sessionId").and_then(Value::as_str)?;
+        let tool_call = params.get("toolCall")?;
+
+        let pending = tool_call_key(session_id, tool_call)
+            .and_then(|key| self.decisions.lock().unwrap().remove(&key));
+        let decision = match pending {
+            Some(decision) => decision.await,
+            None => {
+                let outputs = (self.options.hooks)(self.hook_payload(session_id, tool_call)).await;
+                crate::hook::permission_decision(&outputs)
+            }
+        };
+        let kinds = match decision? {
+            PermissionDecision::Allow => ["allow_once", "allow_always"],
+            PermissionDecision::Deny => ["reject_once", "reject_always"],
+            PermissionDecision::Ask => return None,
+        };
+        let options = params.get("options").and_then(Value::as_array)?;
+        let option_id = kinds.iter().find_map(|kind| {
+            options
+                .iter()
+                .find(|option| option.get("kind").and_then(Value::as_str) == Some(kind))?
+                .get("optionId")
+                .cloned()
+        })?;
+        tracing::info!(?decision, %option_id, "answered permission request from plugin hooks");
+
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": { "outcome": { "outcome": "selected", "optionId": option_id } },
+        })
+        .as_object()
+        .cloned()
+    }
+
+    /// Add the Symposium MCP server to a session's `mcpServers`, unless the
+    /// editor already configured one with the same name.
+    fn add_mcp_server(&self, params: &mut Map<String, Value>) {
+        let servers = params
+            .entry("mcpServers")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        let Some(servers) = servers.as_array_mut() else {
+            return;
+        };
+        if servers
+            .iter()
+            .any(|server| server.get("name").and_then(Value::as_str) == Some(MCP_SERVER_NAME))
+        {
+            return;
+        }
+        servers.push(json!({
+            "name": MCP_SERVER_NAME,
+            "command": self.options.symposium_exe,
+            "args": ["mcp"],
+            "env": [],
+        }));
+    }
+
+    /// Prefix the first prompt of a session with its workspace's skill context.
+    async fn add_context(&self, params: &mut Map<String, Value>) {
+        let Some(session_id) = params.get("sessionId").and_then(Value::as_str) else {
+            return;
+        };
+        let cwd = {
+            let mut sessions = self.sessions.lock().unwrap();
+            if !sessions.prompted.insert(session_id.to_string()) {
+                return;
+            }
+            sessions.cwd.get(session_id).cloned()
+        };
+        let Some(cwd) = cwd else {
+            return;
+        };
+        let Some(context) = (self.options.context)(cwd).await else {
+            return;
+        };
+        if let Some(prompt) = params.get_mut("prompt").and_then(Value::as_array_mut) {
+            prompt.insert(0, json!({ "type": "text", "text": context }));
+        }
+    }
+
+    /// Build a `PreToolUse` hook payload for a tool call (a `tool_call`
+    /// session update, or the `toolCall` of a permission request).
+    fn hook_payload(&self, session_id: &str, update: &Value) -> HookPayload {
+        let mut rest = Map::new();
+        rest.insert("session_id".to_string(), json!(session_id));
+        if let Some(input) = update.get("rawInput") {
+            rest.insert("tool_input".to_string(), input.clone());
+        }
+        if let Some(id) = update.get("toolCallId") {
+            rest.insert("tool_use_id".to_string(), id.clone());
+        }
+        if let Some(cwd) = self.sessions.lock().unwrap().cwd.get(session_id) {
+            rest.insert("cwd".to_string(), json!(cwd));
+        }
+
+        HookPayload {
+            sub_payload: HookSubPayload::PreToolUse(PreToolUsePayload {
+                tool_name: tool_name(update),
+            }),
+            rest,
+        }
+    }
+}
+
+/// Key identifying a tool call across sessions.
+fn tool_call_key(session_id: &str, tool_call: &Value) -> Option<String> {
+    let id = tool_call.get("toolCallId")?;
+    Some(format!("{session_id}/{id}"))
+}
+
+/// The tool name hook matchers see for an ACP tool call.
+///
+/// Agents wrapping Claude Code report the underlying tool in `_meta`; for
+/// others the ACP tool kind is mapped to the closest Claude Code tool name.
+fn tool_name(update: &Value) -> String {
+    if let Some(name) = update
+        .pointer("/_meta/claudeCode/toolName")
+        .and_then(Value::as_str)
+    {
+        return name.to_string();
+    }
+    let kind = update
+        .get("kind")
+        .and_then(Value::as_str)
+        .unwrap_or("other");
+    match kind {
+        "read" => "Read",
+        "edit" => "Edit",
+        "delete" => "Delete",
+        "move" => "Move",
+        "search" => "Grep",
+        "execute" => "Bash",
+        "fetch" => "WebFetch",
+        "think" => "Think",
+        _ => update
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("Other"),
+    }
+    .to_string()
+}
+
+/// Skill context for a workspace, or `None` outside a Cargo workspace.
+async fn skill_context(cwd: PathBuf) -> Option<String> {
+    let workspace = crate::crate_sources::workspace_semver_pairs(&cwd);
+    if workspace.is_empty() {
+        return None;
+    }
+    let registry = crate::plugins::load_registry();
+    let skills = crate::skills::list_output(&registry, &workspace).await;
+    Some(format_context(&cwd, &skills))
+}
+
+fn format_context(cwd: &Path, skills: &str) -> String {
+    format!(
+        "<symposium>\n\
+         Rust guidance for the workspace at {} is available through the `symposium` \
+         MCP server (`rust`, `crate`, and `cargo` tools). Load a skill with \
+         `crate <name>` before working with that crate.\n\n\
+         {}</symposium>",
+        cwd.display(),
+        skills,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{DuplexStream, Lines};
+
+    /// A minimal agent: answers `session/new` and `session/prompt`. For each
+    /// prompt it reports one tool call and asks permission for it, then
+    /// answers the prompt, echoing the prompt it received and the permission
+    /// outcome.
+    async fn stub_agent(input: DuplexStream, mut output: DuplexStream) {
+        let mut lines = BufReader::new(input).lines();
+        let mut prompt = None;
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let message: Value = serde_json::from_str(&line).unwrap();
+            let id = message["id"].clone();
+            let params = &message["params"];
+            let mut replies = Vec::new();
+            match message["method"].as_str() {
+                Some("session/new") => replies.push(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": { "sessionId": "s1", "mcpServers": params["mcpServers"] },
+                })),
+                Some("session/prompt") => {
+                    let tool_call = json!({
+                        "toolCallId": format!("t{id}"),
+                        "title": "cargo test",
+                        "kind": "execute",
+                        "rawInput": { "command": "cargo test" },
+                    });
+                    let mut update = tool_call.clone();
+                    update["sessionUpdate"] = json!("tool_call");
+                    replies.push(json!({
+                        "jsonrpc": "2.0",
+                        "method": "session/update",
+                        "params": { "sessionId": "s1", "update": update },
+                    }));
+                    replies.push(json!({
+                        "jsonrpc": "2.0",
+                        "id": "permission",
+                        "method": "session/request_permission",
+                        "params": {
+                            "sessionId": "s1",
+                            "toolCall": { "toolCallId": tool_call["toolCallId"] },
+                            "options": [
+                                { "optionId": "yes", "name": "Allow", "kind": "allow_once" },
+                                { "optionId": "no", "name": "Reject", "kind": "reject_once" },
+                            ],
+                        },
+                    }));
+                    prompt = Some((id, params["prompt"].clone()));
+                }
+                None => {
+                    let (id, prompt) = prompt.take().unwrap();
+                    replies.push(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "stopReason": "end_turn",
+                            "prompt": prompt,
+                            "permission": message["result"]["outcome"]["optionId"],
+                        },
+                    }));
+                }
+                Some(method) => panic!("unexpected method {method}"),
+            }
+            for reply in replies {
+                let line = format!("{reply}\n");
+                output.write_all(line.as_bytes()).await.unwrap();
+            }
+        }
+    }
+
+    /// The editor side of a proxy running in front of [`stub_agent`].
+    struct Editor {
+        input: DuplexStream,
+        output: Lines<BufReader<DuplexStream>>,
+        /// Permission requests the editor was asked, and answered with "yes".
+        permission_requests: usize,
+    }
+
+    impl Editor {
+        fn start(options: ProxyOptions) -> (Self, tokio::task::JoinHandle<Result<()>>) {
+            let (input, editor_in) = tokio::io::duplex(64 * 1024);
+            let (editor_out, output) = tokio::io::duplex(64 * 1024);
+            let (agent_in, stub_in) = tokio::io::duplex(64 * 1024);
+            let (stub_out, agent_out) = tokio::io::duplex(64 * 1024);
+            tokio::spawn(stub_agent(stub_in, stub_out));
+            let proxy = tokio::spawn(proxy(
+                BufReader::new(editor_in),
+                editor_out,
+                agent_in,
+                BufReader::new(agent_out),
+                options,
+            ));
+            let editor = Editor {
+                input,
+                output: BufReader::new(output).lines(),
+                permission_requests: 0,
+            };
+            (editor, proxy)
+        }
+
+        async fn send(&mut self, message: Value) {
+            self.input
+                .write_all(format!("{message}\n").as_bytes())
+                .await
+                .unwrap();
+        }
+
+        /// Send a request and return its result.
+        async fn request(&mut self, request: Value) -> Value {
+            self.send(request.clone()).await;
+            loop {
+                let line = self.output.next_line().await.unwrap().unwrap();
+                let message: Value = serde_json::from_str(&line).unwrap();
+                if message["method"] == "session/request_permission" {
+                    self.permission_requests += 1;
+                    let id = message["id"].clone();
+                    self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": { "outcome": { "outcome": "selected", "optionId": "yes" } },
+                    }))
+                    .await;
+                } else if message.get("id") == request.get("id") {
+                    return message["result"].clone();
+                }
+            }
+        }
+    }
+
+    fn test_options(hooks: HookRunner) -> ProxyOptions {
+        ProxyOptions {
+            symposium_exe: PathBuf::from("/bin/symposium"),
+            hooks,
+            context: Arc::new(|cwd| {
+                Box::pin(async move { Some(format!("skills for {}", cwd.display())) })
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn proxies_sessions_through_a_stub_agent() {
+        let hooks = Arc::new(Mutex::new(Vec::new()));
+        let (mut editor, proxy) = Editor::start(test_options({
+            let hooks = hooks.clone();
+            Arc::new(move |payload| {
+                hooks.lock().unwrap().push(payload);
+                Box::pin(async { Vec::new() })
+            })
+        }));
+
+        let session = editor
+            .request(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "session/new",
+                "params": { "cwd": "/work", "mcpServers": [] },
+            }))
+            .await;
+        assert_eq!(
+            session["mcpServers"],
+            json!([{ "name": "symposium", "command": "/bin/symposium", "args": ["mcp"], "env": [] }])
+        );
+
+        let prompt = json!([{ "type": "text", "text": "hello" }]);
+        let first = editor
+            .request(json!({
+                "jsonrpc": "2.0", "id": 2, "method": "session/prompt",
+                "params": { "sessionId": "s1", "prompt": prompt },
+            }))
+            .await;
+        assert_eq!(
+            first["prompt"],
+            json!([
+                { "type": "text", "text": "skills for /work" },
+                { "type": "text", "text": "hello" },
+            ])
+        );
+        // Hooks that print nothing leave the permission to the editor.
+        assert_eq!(first["permission"], "yes");
+        assert_eq!(editor.permission_requests, 1);
+
+        let second = editor
+            .request(json!({
+                "jsonrpc": "2.0", "id": 3, "method": "session/prompt",
+                "params": { "sessionId": "s1", "prompt": prompt },
+            }))
+            .await;
+        assert_eq!(second["prompt"], prompt);
+
+        // Closing the editor's side ends the session and the proxy.
+        drop(editor);
+        proxy.await.unwrap().unwrap();
+
+        // Each tool call ran the hooks once, for the `tool_call` update.
+        let hooks = hooks.lock().unwrap();
+        assert_eq!(hooks.len(), 2);
+        let payload = serde_json::to_value(&hooks[0]).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "session_id": "s1",
+                "tool_input": { "command": "cargo test" },
+                "tool_use_id": "t2",
+                "cwd": "/work",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn hook_denial_rejects_permission_request() {
+        let (mut editor, _proxy) = Editor::start(test_options(Arc::new(|_| {
+            Box::pin(async {
+                vec![HookOutput {
+                    stdout: json!({
+                        "hookSpecificOutput": {
+                            "hookEventName": "PreToolUse",
+                            "permissionDecision": "deny",
+                        }
+                    })
+                    .to_string(),
+                    blocked: false,
+                }]
+            })
+        })));
+
+        editor
+            .request(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "session/new",
+                "params": { "cwd": "/work", "mcpServers": [] },
+            }))
+            .await;
+        let result = editor
+            .request(json!({
+                "jsonrpc": "2.0", "id": 2, "method": "session/prompt",
+                "params": { "sessionId": "s1", "prompt": [] },
+            }))
+            .await;
+        assert_eq!(result["permission"], "no");
+        assert_eq!(editor.permission_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn pending_hooks_do_not_hold_up_agent_messages() {
+        let gate = Arc::new(tokio::sync::Notify::new());
+        let hooks: HookRunner = {
+            let gate = gate.clone();
+            Arc::new(move |_| {
+                let gate = gate.clone();
+                Box::pin(async move {
+                    gate.notified().await;
+                    vec![HookOutput {
+                        stdout: json!({
+                            "hookSpecificOutput": { "permissionDecision": "deny" }
+                        })
+                        .to_string(),
+                        blocked: false,
+                    }]
+                })
+            })
+        };
+        let (replies, mut replies_rx) = mpsc::unbounded_channel();
+        let (forwarded, _forwarded_rx) = mpsc::unbounded_channel();
+        let state = Arc::new(ProxyState::new(test_options(hooks), replies, forwarded));
+        let message = |value: Value| value.as_object().cloned().unwrap();
+
+        let request = message(json!({
+            "jsonrpc": "2.0", "id": 7, "method": "session/request_permission",
+            "params": {
+                "sessionId": "s1",
+                "toolCall": { "toolCallId": "t1" },
+                "options": [{ "optionId": "no", "kind": "reject_once" }],
+            },
+        }));
+        assert_eq!(state.handle_agent_message(request), None);
+
+        // The hooks are still running, and updates still reach the editor.
+        let update = message(json!({
+            "jsonrpc": "2.0", "method": "session/update",
+            "params": { "sessionId": "s1", "update": { "sessionUpdate": "agent_message_chunk" } },
+        }));
+        assert_eq!(state.handle_agent_message(update.clone()), Some(update));
+
+        gate.notify_one();
+        let reply = replies_rx.recv().await.unwrap();
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["result"]["outcome"]["optionId"], "no");
+    }
+
+    #[test]
+    fn tool_names_prefer_claude_code_metadata() {
+        let update =
+            json!({ "kind": "edit", "_meta": { "claudeCode": { "toolName": "MultiEdit" } } });
+        assert_eq!(tool_name(&update), "MultiEdit");
+        assert_eq!(tool_name(&json!({ "kind": "search" })), "Grep");
+        assert_eq!(tool_name(&json!({ "title": "Custom" })), "Custom");
+    }
+}
@@ -0,0 +1,600 @@
+//! Token-optimized cargo output (`symposium cargo`).
+//!
+//! Runs cargo with `--message-format=json` where the subcommand supports it
+//! and turns the result into a compact report: deduplicated and truncated
+//! diagnostics (similar warnings grouped), build progress collapsed into a
+//! count, and test results summarized with the output of failing tests.
+//!
+//! The full text of every diagnostic from the last run in a workspace is
+//! saved under the cache directory, so `symposium cargo --detail <n>` can show
+//! one without rerunning the build.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::Message;
+use cargo_metadata::diagnostic::Diagnostic;
+
+use crate::diagnostics;
+use crate::progress;
+
+/// Subcommands that accept `--message-format=json`.
+const JSON_SUBCOMMANDS: &[&str] = &[
+    "build", "b", "check", "c", "clippy", "test", "t", "bench", "run", "r", "doc", "d", "rustc",
+    "rustdoc", "fix",
+];
+
+/// Diagnostics listed in full before the rest are only counted.
+const MAX_DIAGNOSTICS: usize = 10;
+
+/// Lines of each diagnostic's rendered text shown in the report.
+const EXCERPT_LINES: usize = 8;
+
+/// Lines of each failing test's output shown in the report.
+const FAILURE_LINES: usize = 15;
+
+/// Lines of other output (e.g. from `cargo run`) kept, from the end.
+const OTHER_OUTPUT_LINES: usize = 30;
+
+/// Progress lines cargo prints on stderr.
+const PROGRESS_PREFIXES: &[&str] = &[
+    "Compiling",
+    "Checking",
+    "Documenting",
+    "Downloading",
+    "Downloaded",
+    "Updating",
+    "Locking",
+    "Adding",
+    "Blocking",
+    "Fresh",
+    "Running",
+    "Doc-tests",
+    "Packaging",
+    "Verifying",
+    "Archiving",
+];
+
+/// The parsed result of one cargo invocation.
+struct CargoRun {
+    /// The command line that was run.
+    command: String,
+    success: bool,
+    status: String,
+    /// Errors first, then warnings and notes; deduplicated.
+    diagnostics: Vec<Diagnostic>,
+    /// Number of crates compiled or checked.
+    compiled: usize,
+    /// The `Finished ...` line, if any.
+    finished: Option<String>,
+    tests: TestSummary,
+    /// Output that is neither progress, diagnostics, nor test results.
+    other: Vec<String>,
+}
+
+#[derive(Default)]
+struct TestSummary {
+    /// Number of `test result:` lines seen (one per test binary).
+    suites: usize,
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+    /// Failing test names with their captured output.
+    failures: Vec<(String, Vec<String>)>,
+}
+
+/// Run cargo in `root` and return a compact report.
+pub async fn run_output(root: &Path, args: &[String]) -> Result<String> {
+    let run = run_cargo(root, args).await?;
+    if let Err(e) = save_details(&crate::config::cache_dir(), root, &run.diagnostics) {
+        tracing::warn!(error = %e, "failed to save cargo diagnostic details");
+    }
+    Ok(report(&run))
+}
+
+/// Return the full text of diagnostic `index` (1-based) from the last run in `root`.
+pub fn detail_output(root: &Path, index: usize) -> Result<String> {
+    read_detail(&crate::config::cache_dir(), root, index)
+}
+
+fn read_detail(cache_dir: &Path, root: &Path, index: usize) -> Result<String> {
+    let path = details_path(cache_dir, root);
+    let content = std::fs::read_to_string(&path)
+        .context("no saved diagnostics for this workspace; run `symposium cargo` first")?;
+    let details: Vec<String> = serde_json::from_str(&content)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    match index.checked_sub(1).and_then(|i| details.get(i)) {
+        Some(detail) => Ok(detail.clone()),
+        None => bail!(
+            "no diagnostic #{index}; the last run had {} diagnostic(s)",
+            details.len()
+        ),
+    }
+}
+
+async fn run_cargo(root: &Path, args: &[String]) -> Result<CargoRun> {
+    let Some(subcommand) = args.first() else {
+        bail!("usage: symposium cargo <subcommand> [args...]");
+    };
+
+    let mut command_args = args.to_vec();
+    let wants_json = JSON_SUBCOMMANDS.contains(&subcommand.as_str())
+        && !args.iter().any(|a| a.starts_with("--message-format"));
+    if wants_json {
+        // Insert before any `--` so the flag goes to cargo, not the program.
+        let at = command_args
+            .iter()
+            .position(|a| a == "--")
+            .unwrap_or(command_args.len());
+        command_args.insert(at, "--message-format=json".to_string());
+    }
+    let command = format!("cargo {}", command_args.join(" "));
+
+    progress::report(format!("Running {command}"));
+    let output = tokio::process::Command::new("cargo")
+        .args(&command_args)
+        .current_dir(root)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("failed to run cargo")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(parse_run(
+        command,
+        output.status.success(),
+        output.status.to_string(),
+        &stdout,
+        &stderr,
+    ))
+}
+
+/// Sort cargo's output into diagnostics, progress, test results, and the rest.
+fn parse_run(
+    command: String,
+    success: bool,
+    status: String,
+    stdout: &str,
+    stderr: &str,
+) -> CargoRun {
+    let mut diagnostics = diagnostics::parse_compiler_messages(stdout);
+    diagnostics.sort_by_key(|d| !diagnostics::is_error(d));
+
+    let mut text_lines = Vec::new();
+    for message in Message::parse_stream(stdout.as_bytes()).flatten() {
+        if let Message::TextLine(line) = message {
+            text_lines.push(line);
+        }
+    }
+    let (tests, mut other) = parse_test_output(&text_lines);
+
+    let mut compiled = 0;
+    let mut finished = None;
+    for line in stderr.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("Compiling") || trimmed.starts_with("Checking") {
+            compiled += 1;
+        } else if trimmed.starts_with("Finished") {
+            finished = Some(trimmed.to_string());
+        } else if PROGRESS_PREFIXES.iter().any(|p| trimmed.starts_with(p)) {
+            // Collapsed.
+        } else if !trimmed.is_empty() {
+            other.push(line.to_string());
+        }
+    }
+
+    CargoRun {
+        command,
+        success,
+        status,
+        diagnostics,
+        compiled,
+        finished,
+        tests,
+        other,
+    }
+}
+
+/// Parse libtest's plain output, returning the summary and the lines that
+/// were not test output.
+fn parse_test_output(lines: &[String]) -> (TestSummary, Vec<String>) {
+    let mut summary = TestSummary::default();
+    let mut other = Vec::new();
+    let mut failure: Option<(String, Vec<String>)> = None;
+
+    for line in lines {
+        // Captured output of a failing test runs until the next header or
+        // the closing `failures:` list.
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            summary.failures.extend(failure.take());
+            failure = Some((name.to_string(), Vec::new()));
+            continue;
+        }
+        if line == "failures:" {
+            summary.failures.extend(failure.take());
+            continue;
+        }
+        if let Some((_, output)) = &mut failure {
+            output.push(line.clone());
+            continue;
+        }
+
+        if let Some(result) = line.strip_prefix("test result: ") {
+            summary.suites += 1;
+            for part in result.split(';') {
+                let mut words = part.split_whitespace().rev();
+                let (Some(kind), Some(count)) = (words.next(), words.next()) else {
+                    continue;
+                };
+                let Ok(count) = count.parse::<usize>() else {
+                    continue;
+                };
+                match kind {
+                    "passed" => summary.passed += count,
+                    "failed" => summary.failed += count,
+                    "ignored" => summary.ignored += count,
+                    _ => {}
+                }
+            }
+        } else if line.starts_with("test ")
+            || line.starts_with("running ")
+            || line.starts_with("    ")
+            || line.is_empty()
+        {
+            // Per-test status lines, headers, and the `failures:` name list.
+        } else {
+            other.push(line.clone());
+        }
+    }
+    summary.failures.extend(failure);
+
+    for (_, output) in &mut summary.failures {
+        *output = compact_failure(output);
+    }
+    (summary, other)
+}
+
+/// Trim blank lines around a failing test's output and replace stack
+/// backtraces with a one-line note.
+fn compact_failure(output: &[String]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_backtrace = false;
+    for line in output {
+        if line == "stack backtrace:" {
+            in_backtrace = true;
+            lines.push("(stack backtrace omitted)".to_string());
+            continue;
+        }
+        if in_backtrace && line.starts_with(' ') {
+            continue;
+        }
+        in_backtrace = false;
+        lines.push(line.clone());
+    }
+
+    let start = lines.iter().position(|l| !l.trim().is_empty());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Render a run as a compact report.
+fn report(run: &CargoRun) -> String {
+    let mut out = format!("$ {}\n", run.command);
+    if run.success {
+        out.push_str("Result: succeeded\n");
+    } else {
+        out.push_str(&format!("Result: failed ({})\n", run.status));
+    }
+    if run.compiled > 0 {
+        out.push_str(&format!("Compiled {} crate(s)\n", run.compiled));
+    }
+    if let Some(finished) = &run.finished {
+        out.push_str(&format!("{finished}\n"));
+    }
+
+    if !run.diagnostics.is_empty() {
+        out.push_str(&report_diagnostics(&run.diagnostics));
+    }
+
+    let tests = &run.tests;
+    if tests.suites > 0 {
+        out.push_str(&format!(
+            "\nTests: {} passed, {} failed, {} ignored\n",
+            tests.passed, tests.failed, tests.ignored
+        ));
+        for (name, output) in &tests.failures {
+            out.push_str(&format!("\nFAILED {name}\n"));
+            for line in output.iter().take(FAILURE_LINES) {
+                out.push_str(&format!("    {line}\n"));
+            }
+            if output.len() > FAILURE_LINES {
+                out.push_str(&format!(
+                    "    ... {} more line(s)\n",
+                    output.len() - FAILURE_LINES
+                ));
+            }
+        }
+    }
+
+    if !run.other.is_empty() {
+        out.push_str("\nOutput:\n");
+        let skip = run.other.len().saturating_sub(OTHER_OUTPUT_LINES);
+        if skip > 0 {
+            out.push_str(&format!("    ... {skip} earlier line(s)\n"));
+        }
+        for line in &run.other[skip..] {
+            out.push_str(&format!("    {line}\n"));
+        }
+    }
+
+    out
+}
+
+/// List diagnostics, grouping warnings with the same lint or message.
+fn report_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    let errors = diagnostics
+        .iter()
+        .filter(|d| diagnostics::is_error(d))
+        .count();
+    let mut out = format!(
+        "\nDiagnostics: {errors} error(s), {} warning(s)/note(s)\n",
+        diagnostics.len() - errors
+    );
+
+    // Group by lint code (or message), keeping first-seen order; errors are
+    // never grouped.
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of: BTreeMap<String, usize> = BTreeMap::new();
+    for (index, diagnostic) in diagnostics.iter().enumerate() {
+        if diagnostics::is_error(diagnostic) {
+            groups.push(vec![index]);
+            continue;
+        }
+        let key = match &diagnostic.code {
+            Some(code) => code.code.clone(),
+            None => diagnostic.message.clone(),
+        };
+        match group_of.get(&key) {
+            Some(&group) => groups[group].push(index),
+            None => {
+                group_of.insert(key, groups.len());
+                groups.push(vec![index]);
+            }
+        }
+    }
+
+    for group in groups.iter().take(MAX_DIAGNOSTICS) {
+        let diagnostic = &diagnostics[group[0]];
+        out.push_str(&format!(
+            "\n[{}] {}\n",
+            group[0] + 1,
+            diagnostics::headline(diagnostic)
+        ));
+        if let Some(location) = diagnostics::primary_location(diagnostic) {
+            out.push_str(&format!("    --> {location}\n"));
+        }
+        if diagnostics::is_error(diagnostic) {
+            for line in excerpt(diagnostic) {
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+        if group.len() > 1 {
+            let others: Vec<String> = group[1..]
+                .iter()
+                .map(|&i| {
+                    let location = diagnostics::primary_location(&diagnostics[i]);
+                    format!("[{}] {}", i + 1, location.as_deref().unwrap_or("?"))
+                })
+                .collect();
+            out.push_str(&format!(
+                "    (+{} more like this: {})\n",
+                others.len(),
+                others.join(", ")
+            ));
+        }
+    }
+    if groups.len() > MAX_DIAGNOSTICS {
+        let hidden: usize = groups[MAX_DIAGNOSTICS..].iter().map(Vec::len).sum();
+        out.push_str(&format!("\n... and {hidden} more diagnostic(s)\n"));
+    }
+    out.push_str("\nUse `symposium cargo --detail <n>` for the full text of diagnostic [n].\n");
+    out
+}
+
+/// The body of a diagnostic's rendered text: without the headline and the
+/// `-->` line (already shown), and truncated.
+fn excerpt(diagnostic: &Diagnostic) -> Vec<String> {
+    let Some(rendered) = &diagnostic.rendered else {
+        return Vec::new();
+    };
+    let body: Vec<&str> = rendered
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim_start().starts_with("-->"))
+        .collect();
+    let mut lines: Vec<String> = body
+        .iter()
+        .take(EXCERPT_LINES)
+        .map(|line| line.to_string())
+        .collect();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    if body.len() > EXCERPT_LINES {
+        lines.push("...".to_string());
+    }
+    lines
+}
+
+/// Where the diagnostics of the last run in `root` are saved, named by
+/// [`workspace_hash`](crate::server_cache::workspace_hash).
+fn details_path(cache_dir: &Path, root: &Path) -> PathBuf {
+    let hash = crate::server_cache::workspace_hash(root);
+    cache_dir.join("cargo").join(format!("{hash:016x}.json"))
+}
+
+fn save_details(cache_dir: &Path, root: &Path, diagnostics: &[Diagnostic]) -> Result<()> {
+    let details: Vec<String> = diagnostics
+        .iter()
+        .map(|d| {
+            d.rendered
+                .clone()
+                .unwrap_or_else(|| diagnostics::headline(d))
+        })
+        .collect();
+    let path = details_path(cache_dir, root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string(&details)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn compiler_message(level: &str, code: Option<&str>, message: &str, line: usize) -> String {
+        serde_json::json!({
+            "reason": "compiler-message",
+            "package_id": "app 0.1.0 (path+file:///work/app)",
+            "manifest_path": "/work/app/Cargo.toml",
+            "target": {
+                "kind": ["lib"], "crate_types": ["lib"], "name": "app",
+                "src_path": "/work/app/src/lib.rs", "edition": "2021",
+                "doc": true, "doctest": true, "test": true
+            },
+            "message": {
+                "message": message,
+                "code": code.map(|code| serde_json::json!({ "code": code, "explanation": null })),
+                "level": level,
+                "spans": [{
+                    "file_name": "src/lib.rs", "byte_start": 0, "byte_end": 1,
+                    "line_start": line, "line_end": line, "column_start": 9, "column_end": 10,
+                    "is_primary": true, "text": [], "label": null,
+                    "suggested_replacement": null, "suggestion_applicability": null,
+                    "expansion": null
+                }],
+                "children": [],
+                "rendered": format!(
+                    "{level}: {message}\n --> src/lib.rs:{line}:9\n  |\n{line} |     code\n  |     ^\n"
+                ),
+            },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn report_groups_warnings_and_summarizes_tests() {
+        let stdout = [
+            compiler_message("warning", Some("unused_variables"), "unused variable: `a`", 3),
+            compiler_message("error", Some("E0308"), "mismatched types", 7),
+            compiler_message("warning", Some("unused_variables"), "unused variable: `b`", 4),
+            compiler_message("warning", Some("unused_variables"), "unused variable: `b`", 4),
+            indoc! {"
+                running 3 tests
+                test tests::a ... ok
+                test tests::b ... FAILED
+                test tests::c ... ignored
+
+                failures:
+
+                ---- tests::b stdout ----
+
+                thread 'tests::b' panicked at src/lib.rs:20:9:
+                assertion failed: false
+                stack backtrace:
+                   0: rust_begin_unwind
+                             at /rustc/library/std/src/panicking.rs:665:5
+                note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+
+
+                failures:
+                    tests::b
+
+                test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+            "}
+            .to_string(),
+        ]
+        .join("\n");
+        let stderr = indoc! {"
+               Compiling dep v1.0.0
+               Compiling app v0.1.0 (/work/app)
+                Finished `test` profile [unoptimized + debuginfo] target(s) in 1.00s
+                 Running unittests src/lib.rs (target/debug/deps/app-123)
+            error: test failed, to rerun pass `--lib`
+        "};
+
+        let run = parse_run(
+            "cargo test --message-format=json".to_string(),
+            false,
+            "exit status: 101".to_string(),
+            &stdout,
+            stderr,
+        );
+        expect_test::expect![[r#"
+            $ cargo test --message-format=json
+            Result: failed (exit status: 101)
+            Compiled 2 crate(s)
+            Finished `test` profile [unoptimized + debuginfo] target(s) in 1.00s
+
+            Diagnostics: 1 error(s), 2 warning(s)/note(s)
+
+            [1] error[E0308]: mismatched types
+                --> src/lib.rs:7:9
+                  |
+                7 |     code
+                  |     ^
+
+            [2] warning[unused_variables]: unused variable: `a`
+                --> src/lib.rs:3:9
+                (+1 more like this: [3] src/lib.rs:4:9)
+
+            Use `symposium cargo --detail <n>` for the full text of diagnostic [n].
+
+            Tests: 1 passed, 1 failed, 1 ignored
+
+            FAILED tests::b
+                thread 'tests::b' panicked at src/lib.rs:20:9:
+                assertion failed: false
+                (stack backtrace omitted)
+                note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+
+            Output:
+                error: test failed, to rerun pass `--lib`
+        "#]]
+        .assert_eq(&report(&run));
+    }
+
+    #[test]
+    fn details_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = tmp.path().join("cache");
+        let stdout = compiler_message("error", Some("E0308"), "mismatched types", 7);
+        let diagnostics = diagnostics::parse_compiler_messages(&stdout);
+
+        save_details(&cache, tmp.path(), &diagnostics).unwrap();
+        let detail = read_detail(&cache, tmp.path(), 1).unwrap();
+        assert!(detail.starts_with("error: mismatched types\n --> src/lib.rs:7:9"));
+        assert!(read_detail(&cache, tmp.path(), 2).is_err());
+        assert!(read_detail(&cache, tmp.path(), 0).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn details_path_is_stable() {
+        assert_eq!(
+            details_path(Path::new("/cache"), Path::new("/nonexistent/workspace")),
+            Path::new("/cache/cargo/2ea3c322acf5b8f7.json")
+        );
+    }
+}
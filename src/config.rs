@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::Level;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -20,6 +21,17 @@ pub struct Config {
     /// User-defined plugin sources (git repos or local paths).
     #[serde(default, rename = "plugin-source")]
     pub plugin_source: Vec<PluginSourceConfig>,
+
+    /// Crate guidance output settings.
+    #[serde(default)]
+    pub guidance: GuidanceConfig,
+}
+
+/// Settings for crate guidance output.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct GuidanceConfig {
+    /// Default limit on guidance output, in approximate tokens (default: none).
+    pub budget: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -43,6 +55,7 @@ impl Default for Config {
             cache_dir: None,
             defaults: DefaultsConfig::default(),
             plugin_source: Vec::new(),
+            guidance: GuidanceConfig::default(),
         }
     }
 }
@@ -85,6 +98,24 @@ pub struct PluginSourceConfig {
     /// Whether to auto-update on startup (git sources only, default: true).
     #[serde(default = "default_true", rename = "auto-update")]
     pub auto_update: bool,
+
+    /// Which source wins when several provide a skill with the same name.
+    #[serde(default)]
+    pub precedence: Precedence,
+}
+
+/// Precedence of a plugin source, lowest first. When skills from several
+/// sources share a name, the one from the highest-precedence source is used.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Precedence {
+    /// Built-in sources (`symposium-recommendations`).
+    Builtin,
+    /// Organization sources: the default for `[[plugin-source]]` entries.
+    #[default]
+    Org,
+    /// User and project sources (`user-plugins`).
+    User,
 }
 
 /// Initialize logging and config. Call once at startup.
@@ -167,6 +198,7 @@ pub fn plugin_sources() -> Vec<PluginSourceConfig> {
                 git: Some(BUILTIN_RECOMMENDATIONS_URL.to_string()),
                 path: None,
                 auto_update: true,
+                precedence: Precedence::Builtin,
             });
         }
 
@@ -176,6 +208,7 @@ pub fn plugin_sources() -> Vec<PluginSourceConfig> {
                 git: None,
                 path: Some("plugins".to_string()),
                 auto_update: true,
+                precedence: Precedence::User,
             });
         }
 
@@ -184,6 +217,11 @@ pub fn plugin_sources() -> Vec<PluginSourceConfig> {
     })
 }
 
+/// Returns the configured default budget for guidance output, if any.
+pub fn guidance_budget() -> Option<usize> {
+    with_config(|c| c.guidance.budget)
+}
+
 #[cfg(test)]
 pub fn plugins_dir() -> PathBuf {
     let dir = config_dir().join("plugins");
@@ -191,13 +229,22 @@ pub fn plugins_dir() -> PathBuf {
     dir
 }
 
+/// Discard the loaded config so the next access re-reads `config.toml`.
+///
+/// The config is cached per thread; bumping the generation makes every
+/// thread reload on its next access.
+pub fn reload() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
 fn with_config<T>(f: impl FnOnce(&Config) -> T) -> T {
+    let generation = GENERATION.load(Ordering::SeqCst);
     CONFIG.with(|cell| {
         let mut opt = cell.borrow_mut();
-        if opt.is_none() {
-            *opt = Some(load_config());
+        if opt.as_ref().is_none_or(|(g, _)| *g != generation) {
+            *opt = Some((generation, load_config()));
         }
-        f(opt.as_ref().unwrap())
+        f(&opt.as_ref().unwrap().1)
     })
 }
 
@@ -213,7 +260,7 @@ fn load_config() -> Config {
 }
 
 /// Returns the path to the config file.
-fn config_path() -> PathBuf {
+pub fn config_path() -> PathBuf {
     config_dir().join("config.toml")
 }
 
@@ -259,8 +306,11 @@ fn default_home() -> PathBuf {
 
 const BUILTIN_RECOMMENDATIONS_URL: &str = "https://github.com/symposium-dev/recommendations";
 
+/// Incremented by [`reload`] to invalidate every thread's cached config.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
 thread_local! {
-    static CONFIG: RefCell<Option<Config>> = const { RefCell::new(None) };
+    static CONFIG: RefCell<Option<(u64, Config)>> = const { RefCell::new(None) };
 }
 
 fn default_true() -> bool {
@@ -335,6 +385,33 @@ mod tests {
         assert_eq!(config.plugin_source.len(), 1);
         assert_eq!(config.plugin_source[0].path.as_deref(), Some("my-plugins"));
         assert!(config.plugin_source[0].auto_update); // default true
+        assert_eq!(config.plugin_source[0].precedence, Precedence::Org);
+    }
+
+    #[test]
+    fn parse_plugin_source_precedence() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [[plugin-source]]
+            name = "project"
+            path = "/work/project/.symposium"
+            precedence = "user"
+        "#})
+        .unwrap();
+        assert_eq!(config.plugin_source[0].precedence, Precedence::User);
+        assert!(Precedence::User > Precedence::Org && Precedence::Org > Precedence::Builtin);
+    }
+
+    #[test]
+    fn parse_guidance_budget() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.guidance.budget, None);
+
+        let config: Config = toml::from_str(indoc! {"
+            [guidance]
+            budget = 4000
+        "})
+        .unwrap();
+        assert_eq!(config.guidance.budget, Some(4000));
     }
 
     #[test]
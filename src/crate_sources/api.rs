@@ -0,0 +1,612 @@
+//! Public API outline extracted from crate sources
+//!
+//! Parses the crate's library root with `syn`, follows `pub mod` declarations
+//! into their files, and renders the public items as a Rust skeleton: bodies
+//! are elided, doc comments are reduced to their first line, and `#[cfg]`
+//! attributes are kept so feature-gated items are visible.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Indentation used for nested items in the outline.
+const INDENT: &str = "    ";
+
+/// Fetch a crate's sources and format an outline of its public API.
+pub async fn api_output(
+    name: &str,
+    version: Option<&str>,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let result = super::fetch_crate(name, version, workspace).await?;
+
+    let mut output = format!(
+        "Crate: {}\nVersion: {}\nSource: {}\n\n",
+        result.name,
+        result.version,
+        result.path.display()
+    );
+    output.push_str(&outline_crate(&result.path)?);
+    Ok(output)
+}
+
+/// Produce the public API outline for the crate rooted at `root`.
+pub fn outline_crate(root: &Path) -> Result<String> {
+    let lib_path = library_root(root)?;
+    let file = parse_file(&lib_path)?;
+
+    let mut outliner = Outliner {
+        out: String::new(),
+        visited: HashSet::from([canonical(&lib_path)]),
+    };
+    if let Some(summary) = doc_summary(&file.attrs) {
+        outliner.out.push_str(&format!("//! {summary}\n\n"));
+    }
+    let mod_dir = lib_path.parent().unwrap_or(root).to_path_buf();
+    outliner.items(&file.items, &mod_dir, 0);
+    Ok(outliner.out)
+}
+
+/// Locate the library root: `[lib] path` from `Cargo.toml`, or `src/lib.rs`.
+fn library_root(root: &Path) -> Result<PathBuf> {
+    let manifest = std::fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|s| toml::from_str::<toml::Value>(&s).ok());
+    let configured = manifest
+        .as_ref()
+        .and_then(|m| m.get("lib"))
+        .and_then(|lib| lib.get("path"))
+        .and_then(|p| p.as_str());
+
+    let path = root.join(configured.unwrap_or("src/lib.rs"));
+    if !path.is_file() {
+        anyhow::bail!("crate has no library target (expected {})", path.display());
+    }
+    Ok(path)
+}
+
+fn parse_file(path: &Path) -> Result<syn::File> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    syn::parse_file(&content).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+struct Outliner {
+    out: String,
+    /// Module files already outlined, so `#[path]` cycles terminate.
+    visited: HashSet<PathBuf>,
+}
+
+impl Outliner {
+    /// Outline the public items of a module whose children live in `mod_dir`.
+    fn items(&mut self, items: &[syn::Item], mod_dir: &Path, depth: usize) {
+        for item in items {
+            match item {
+                syn::Item::Mod(item_mod) => self.module(item_mod, mod_dir, depth),
+                item => {
+                    if let Some(rendered) = outline_item(item) {
+                        self.push_rendered(&rendered, depth);
+                    }
+                }
+            }
+        }
+    }
+
+    fn module(&mut self, item_mod: &syn::ItemMod, mod_dir: &Path, depth: usize) {
+        if !is_public(&item_mod.vis) || is_doc_hidden(&item_mod.attrs) {
+            return;
+        }
+
+        let name = item_mod.ident.to_string();
+        let indent = INDENT.repeat(depth);
+
+        // Inline modules carry their items; file modules must be loaded.
+        let (inner_attrs, items, child_dir) = match &item_mod.content {
+            Some((_, items)) => (Vec::new(), items.clone(), mod_dir.join(&name)),
+            None => match module_file(mod_dir, &name, &item_mod.attrs) {
+                Some(path) if !self.visited.insert(canonical(&path)) => {
+                    self.out.push_str(&format!(
+                        "{indent}// pub mod {name}: module file already outlined\n"
+                    ));
+                    return;
+                }
+                Some(path) => match parse_file(&path) {
+                    Ok(file) => {
+                        let child_dir = if is_mod_rs(&path) {
+                            path.parent().unwrap_or(mod_dir).to_path_buf()
+                        } else {
+                            mod_dir.join(&name)
+                        };
+                        (file.attrs, file.items, child_dir)
+                    }
+                    Err(e) => {
+                        self.out
+                            .push_str(&format!("{indent}// pub mod {name}: {e:#}\n"));
+                        return;
+                    }
+                },
+                None => {
+                    self.out.push_str(&format!(
+                        "{indent}// pub mod {name}: source file not found\n"
+                    ));
+                    return;
+                }
+            },
+        };
+
+        let summary = doc_summary(&item_mod.attrs).or_else(|| doc_summary(&inner_attrs));
+        if let Some(summary) = summary {
+            self.out.push_str(&format!("{indent}/// {summary}\n"));
+        }
+        for attr in cfg_attrs(&item_mod.attrs) {
+            self.out
+                .push_str(&format!("{indent}{}\n", render_attr(&attr)));
+        }
+        self.out.push_str(&format!("{indent}pub mod {name} {{\n"));
+        self.items(&items, &child_dir, depth + 1);
+        self.out.push_str(&format!("{indent}}}\n"));
+    }
+
+    fn push_rendered(&mut self, rendered: &str, depth: usize) {
+        let indent = INDENT.repeat(depth);
+        for line in rendered.lines() {
+            if line.is_empty() {
+                self.out.push('\n');
+            } else {
+                self.out.push_str(&format!("{indent}{line}\n"));
+            }
+        }
+    }
+}
+
+/// Find the file for `mod name;` declared in a module whose children live in `mod_dir`.
+fn module_file(mod_dir: &Path, name: &str, attrs: &[syn::Attribute]) -> Option<PathBuf> {
+    let explicit = attrs.iter().find_map(|attr| {
+        if !attr.path().is_ident("path") {
+            return None;
+        }
+        match &attr.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        }
+    });
+    if let Some(explicit) = explicit {
+        let path = mod_dir.join(explicit);
+        return path.is_file().then_some(path);
+    }
+
+    [
+        mod_dir.join(format!("{name}.rs")),
+        mod_dir.join(name).join("mod.rs"),
+    ]
+    .into_iter()
+    .find(|p| p.is_file())
+}
+
+fn is_mod_rs(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|f| f == "mod.rs" || f == "lib.rs" || f == "main.rs")
+}
+
+/// Canonical form of a module file path, for cycle detection.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Render a single non-module item, or `None` if it is not part of the public API.
+fn outline_item(item: &syn::Item) -> Option<String> {
+    use syn::Item;
+
+    let item = match item {
+        Item::Const(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            i.expr = Box::new(elided_expr());
+            Item::Const(i)
+        }
+        Item::Static(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            i.expr = Box::new(elided_expr());
+            Item::Static(i)
+        }
+        Item::Enum(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            for variant in &mut i.variants {
+                variant.attrs = outline_attrs(&variant.attrs).unwrap_or_default();
+                variant.discriminant = None;
+                strip_field_attrs(&mut variant.fields);
+            }
+            Item::Enum(i)
+        }
+        Item::Fn(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            i.block = Box::new(empty_block());
+            Item::Fn(i)
+        }
+        Item::Impl(i) if i.trait_.is_none() => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            i.items.retain_mut(|impl_item| match impl_item {
+                syn::ImplItem::Fn(f) if is_public(&f.vis) => match outline_attrs(&f.attrs) {
+                    Some(attrs) => {
+                        f.attrs = attrs;
+                        f.block = empty_block();
+                        true
+                    }
+                    None => false,
+                },
+                syn::ImplItem::Const(c) if is_public(&c.vis) => match outline_attrs(&c.attrs) {
+                    Some(attrs) => {
+                        c.attrs = attrs;
+                        c.expr = elided_expr();
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            });
+            if i.items.is_empty() {
+                return None;
+            }
+            Item::Impl(i)
+        }
+        Item::Macro(i) if i.ident.is_some() && has_attr(&i.attrs, "macro_export") => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            i.mac.tokens = Default::default();
+            Item::Macro(i)
+        }
+        Item::Struct(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            if let syn::Fields::Named(named) = &mut i.fields {
+                named.named = std::mem::take(&mut named.named)
+                    .into_iter()
+                    .filter(|f| is_public(&f.vis) && !is_doc_hidden(&f.attrs))
+                    .collect();
+            }
+            strip_field_attrs(&mut i.fields);
+            Item::Struct(i)
+        }
+        Item::Trait(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            i.items.retain_mut(|trait_item| match trait_item {
+                syn::TraitItem::Fn(f) => match outline_attrs(&f.attrs) {
+                    Some(attrs) => {
+                        f.attrs = attrs;
+                        f.default = None;
+                        f.semi_token = Some(Default::default());
+                        true
+                    }
+                    None => false,
+                },
+                syn::TraitItem::Type(t) => match outline_attrs(&t.attrs) {
+                    Some(attrs) => {
+                        t.attrs = attrs;
+                        t.default = None;
+                        true
+                    }
+                    None => false,
+                },
+                syn::TraitItem::Const(c) => match outline_attrs(&c.attrs) {
+                    Some(attrs) => {
+                        c.attrs = attrs;
+                        c.default = None;
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            });
+            Item::Trait(i)
+        }
+        Item::Type(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            Item::Type(i)
+        }
+        Item::Use(i) if is_public(&i.vis) => {
+            let mut i = i.clone();
+            i.attrs = outline_attrs(&i.attrs)?;
+            Item::Use(i)
+        }
+        _ => return None,
+    };
+
+    Some(render_item(item))
+}
+
+/// Reduce attributes to what the outline shows: a one-line doc summary and
+/// `#[cfg]` gates. Returns `None` for `#[doc(hidden)]` items.
+fn outline_attrs(attrs: &[syn::Attribute]) -> Option<Vec<syn::Attribute>> {
+    if is_doc_hidden(attrs) {
+        return None;
+    }
+    let mut kept = Vec::new();
+    if let Some(summary) = doc_summary(attrs) {
+        // Leading space so it renders as `/// summary`.
+        let summary = format!(" {summary}");
+        kept.push(syn::parse_quote!(#[doc = #summary]));
+    }
+    kept.extend(cfg_attrs(attrs));
+    Some(kept)
+}
+
+/// Attributes that gate an item on configuration: `#[cfg(..)]`, plus the
+/// `#[doc(cfg(..))]` annotations crates use to document feature requirements.
+fn cfg_attrs(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
+    let mut out = Vec::new();
+    for attr in attrs {
+        if attr.path().is_ident("cfg") {
+            out.push(attr.clone());
+        } else if attr.path().is_ident("cfg_attr") || attr.path().is_ident("doc") {
+            // `#[cfg_attr(docsrs, doc(cfg(feature = "x")))]` or `#[doc(cfg(..))]`:
+            // surface the inner cfg as a plain `#[cfg(..)]`.
+            let mut inner_cfg = None;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("doc") {
+                    meta.parse_nested_meta(|doc_meta| {
+                        if doc_meta.path.is_ident("cfg") {
+                            let content;
+                            syn::parenthesized!(content in doc_meta.input);
+                            let tokens: proc_macro2::TokenStream = content.parse()?;
+                            inner_cfg = Some(tokens);
+                        }
+                        Ok(())
+                    })
+                } else if meta.path.is_ident("cfg") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let tokens: proc_macro2::TokenStream = content.parse()?;
+                    inner_cfg = Some(tokens);
+                    Ok(())
+                } else {
+                    // Skip any other nested meta (e.g. the `docsrs` predicate).
+                    if meta.input.peek(syn::token::Paren) {
+                        let _content;
+                        syn::parenthesized!(_content in meta.input);
+                    } else if meta.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = meta.value()?.parse()?;
+                    }
+                    Ok(())
+                }
+            });
+            if let Some(tokens) = inner_cfg {
+                out.push(syn::parse_quote!(#[cfg(#tokens)]));
+            }
+        }
+    }
+    out
+}
+
+fn strip_field_attrs(fields: &mut syn::Fields) {
+    for field in fields.iter_mut() {
+        field.attrs = outline_attrs(&field.attrs).unwrap_or_default();
+    }
+}
+
+/// Return the first non-empty line of an item's doc comment.
+fn doc_summary(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|l| l.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .find(|line| !line.is_empty())
+}
+
+fn is_public(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(name))
+}
+
+fn is_doc_hidden(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if !attr.path().is_ident("doc") {
+            return false;
+        }
+        let mut hidden = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("hidden") {
+                hidden = true;
+            }
+            Ok(())
+        });
+        hidden
+    })
+}
+
+fn empty_block() -> syn::Block {
+    syn::parse_quote!({})
+}
+
+/// Placeholder expression for elided `const`/`static` values.
+fn elided_expr() -> syn::Expr {
+    syn::parse_quote!(..)
+}
+
+/// Render an item with `prettyplease`, turning elided bodies into `;`.
+fn render_item(item: syn::Item) -> String {
+    let file = syn::File {
+        shebang: None,
+        attrs: Vec::new(),
+        items: vec![item],
+    };
+    let rendered = prettyplease::unparse(&file);
+
+    let mut out = String::new();
+    for line in rendered.lines() {
+        let line = match line.strip_suffix(" {}") {
+            Some(sig) if sig.contains("fn ") => format!("{sig};"),
+            _ => line.to_string(),
+        };
+        let line = line.replace(" = ..;", ";");
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+fn render_attr(attr: &syn::Attribute) -> String {
+    let item: syn::Item = syn::parse_quote!(#attr struct __Outline;);
+    render_item(item)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::fs;
+
+    #[test]
+    fn outline_public_items() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        fs::create_dir_all(src.join("de")).unwrap();
+        fs::write(
+            src.join("lib.rs"),
+            indoc! {r#"
+                //! Widget library.
+                //!
+                //! More details here.
+
+                /// Deserialization support.
+                pub mod de;
+                mod private;
+
+                pub use de::Deserialize;
+
+                /// The maximum number of widgets.
+                pub const MAX: usize = 10;
+
+                /// A widget.
+                ///
+                /// Widgets are great.
+                pub struct Widget {
+                    /// The widget's name.
+                    pub name: String,
+                    secret: u32,
+                }
+
+                impl Widget {
+                    /// Create a widget.
+                    pub fn new(name: &str) -> Self {
+                        todo!()
+                    }
+
+                    fn helper(&self) {}
+                }
+
+                #[cfg(feature = "async")]
+                pub async fn spawn_widget() {}
+
+                #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+                pub enum Shape {
+                    Round,
+                    Square(u32),
+                }
+
+                #[doc(hidden)]
+                pub fn internal() {}
+
+                pub(crate) fn crate_only() {}
+            "#},
+        )
+        .unwrap();
+        fs::write(
+            src.join("de").join("mod.rs"),
+            indoc! {r#"
+                pub trait Deserialize: Sized {
+                    /// Deserialize a value.
+                    fn deserialize(input: &str) -> Result<Self, String> {
+                        unimplemented!()
+                    }
+                }
+            "#},
+        )
+        .unwrap();
+
+        expect_test::expect![[r#"
+            //! Widget library.
+
+            /// Deserialization support.
+            pub mod de {
+                pub trait Deserialize: Sized {
+                    /// Deserialize a value.
+                    fn deserialize(input: &str) -> Result<Self, String>;
+                }
+            }
+            pub use de::Deserialize;
+            /// The maximum number of widgets.
+            pub const MAX: usize;
+            /// A widget.
+            pub struct Widget {
+                /// The widget's name.
+                pub name: String,
+            }
+            impl Widget {
+                /// Create a widget.
+                pub fn new(name: &str) -> Self;
+            }
+            #[cfg(feature = "async")]
+            pub async fn spawn_widget();
+            #[cfg(feature = "serde")]
+            pub enum Shape {
+                Round,
+                Square(u32),
+            }
+        "#]]
+        .assert_eq(&outline_crate(tmp.path()).unwrap());
+    }
+
+    #[test]
+    fn outline_stops_at_path_cycles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        fs::create_dir_all(src.join("a")).unwrap();
+        fs::write(src.join("lib.rs"), "#[path = \"a.rs\"]\npub mod a;\n").unwrap();
+        fs::write(src.join("a.rs"), "#[path = \"../lib.rs\"]\npub mod back;\n").unwrap();
+
+        let outline = outline_crate(tmp.path()).unwrap();
+        assert!(outline.contains("pub mod a {"), "got: {outline}");
+        assert!(outline.contains("already outlined"), "got: {outline}");
+    }
+
+    #[test]
+    fn outline_requires_library_target() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = outline_crate(tmp.path()).unwrap_err();
+        assert!(err.to_string().contains("no library target"), "got: {err}");
+    }
+}
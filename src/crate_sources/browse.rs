@@ -0,0 +1,227 @@
+//! Reading files and listing the layout of extracted crate sources
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+/// Maximum number of lines returned by a single read.
+const MAX_READ_LINES: usize = 2000;
+
+/// Fetch a crate's sources and format a range of lines from one of its files.
+///
+/// `start_line` and `end_line` are 1-based and inclusive. When omitted, the
+/// read starts at the first line and covers at most `MAX_READ_LINES` lines.
+pub async fn read_output(
+    name: &str,
+    version: Option<&str>,
+    path: &str,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let result = super::fetch_crate(name, version, workspace).await?;
+    let file = resolve_crate_path(&result.path, path)?;
+    if file.is_dir() {
+        bail!("`{path}` is a directory; use the `Tree` command to list files");
+    }
+
+    let content =
+        std::fs::read_to_string(&file).with_context(|| format!("failed to read `{path}`"))?;
+    if content.contains('\0') {
+        bail!("`{path}` appears to be a binary file");
+    }
+
+    let mut output = format!(
+        "Crate: {}\nVersion: {}\nFile: {path}\n",
+        result.name, result.version
+    );
+    output.push_str(&format_line_range(&content, start_line, end_line)?);
+    Ok(output)
+}
+
+/// Fetch a crate's sources and format its file layout as an indented tree.
+pub async fn tree_output(
+    name: &str,
+    version: Option<&str>,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let result = super::fetch_crate(name, version, workspace).await?;
+
+    let mut output = format!(
+        "Crate: {}\nVersion: {}\nSource: {}\n\n",
+        result.name,
+        result.version,
+        result.path.display()
+    );
+    output.push_str(&format_tree(&result.path));
+    Ok(output)
+}
+
+/// Resolve a path relative to a crate root, refusing to escape it.
+///
+/// Rejects absolute paths and `..` components up front, then canonicalizes
+/// the result so that symlinks pointing outside the crate are rejected too.
+pub(crate) fn resolve_crate_path(root: &Path, relative: &str) -> Result<PathBuf> {
+    let relative_path = Path::new(relative);
+    for component in relative_path.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            _ => bail!("path `{relative}` must be relative to the crate root without `..`"),
+        }
+    }
+
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve crate root {}", root.display()))?;
+    let resolved = root
+        .join(relative_path)
+        .canonicalize()
+        .with_context(|| format!("`{relative}` not found in crate sources"))?;
+    if !resolved.starts_with(&root) {
+        bail!("path `{relative}` resolves outside the crate sources");
+    }
+    Ok(resolved)
+}
+
+/// Format the requested line range with right-aligned line numbers.
+fn format_line_range(
+    content: &str,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+) -> Result<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let total = lines.len();
+
+    let start = start_line.unwrap_or(1).max(1);
+    if total > 0 && start > total {
+        bail!("start line {start} is past the end of the file ({total} lines)");
+    }
+    let requested_end = end_line.unwrap_or(usize::MAX).min(total);
+    if requested_end < start && total > 0 {
+        bail!("end line {requested_end} is before start line {start}");
+    }
+    let end = requested_end.min(start.saturating_add(MAX_READ_LINES - 1));
+
+    let mut out = format!("Lines: {start}-{end} of {total}\n\n");
+    let width = end.to_string().len();
+    for (i, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+        out.push_str(&format!("{:>width$}  {line}\n", i + 1));
+    }
+
+    if end < requested_end {
+        out.push_str(&format!(
+            "\nOutput limited to {MAX_READ_LINES} lines; pass `start_line` to read further.\n"
+        ));
+    }
+
+    Ok(out)
+}
+
+/// Render the crate's files as an indented tree, directories suffixed with `/`.
+fn format_tree(root: &Path) -> String {
+    let mut out = String::new();
+    let mut printed_dirs = BTreeSet::new();
+
+    for file in super::search::source_files(root) {
+        let Ok(relative) = file.strip_prefix(root) else {
+            continue;
+        };
+
+        // Print any parent directories not yet shown.
+        let mut prefix = PathBuf::new();
+        let components: Vec<_> = relative.components().collect();
+        for (depth, component) in components.iter().enumerate() {
+            prefix.push(component);
+            let indent = "  ".repeat(depth);
+            if depth + 1 == components.len() {
+                out.push_str(&format!(
+                    "{indent}{}\n",
+                    component.as_os_str().to_string_lossy()
+                ));
+            } else if printed_dirs.insert(prefix.clone()) {
+                out.push_str(&format!(
+                    "{indent}{}/\n",
+                    component.as_os_str().to_string_lossy()
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn sample_crate() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("src/de")).unwrap();
+        fs::write(tmp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(tmp.path().join("src/lib.rs"), "one\ntwo\nthree\nfour\n").unwrap();
+        fs::write(tmp.path().join("src/de/mod.rs"), "mod de;\n").unwrap();
+        tmp
+    }
+
+    #[test]
+    fn resolve_rejects_traversal() {
+        let tmp = sample_crate();
+        let err = resolve_crate_path(tmp.path(), "../etc/passwd").unwrap_err();
+        assert!(err.to_string().contains("without `..`"), "got: {err}");
+
+        let err = resolve_crate_path(tmp.path(), "/etc/passwd").unwrap_err();
+        assert!(err.to_string().contains("without `..`"), "got: {err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_rejects_symlink_escape() {
+        let tmp = sample_crate();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret"), tmp.path().join("link")).unwrap();
+
+        let err = resolve_crate_path(tmp.path(), "link").unwrap_err();
+        assert!(err.to_string().contains("outside"), "got: {err}");
+    }
+
+    #[test]
+    fn resolve_accepts_nested_file() {
+        let tmp = sample_crate();
+        let path = resolve_crate_path(tmp.path(), "./src/de/mod.rs").unwrap();
+        assert!(path.ends_with("src/de/mod.rs"));
+    }
+
+    #[test]
+    fn line_range_is_inclusive() {
+        expect_test::expect![[r#"
+            Lines: 2-3 of 4
+
+            2  two
+            3  three
+        "#]]
+        .assert_eq(&format_line_range("one\ntwo\nthree\nfour\n", Some(2), Some(3)).unwrap());
+    }
+
+    #[test]
+    fn line_range_past_end_is_an_error() {
+        assert!(format_line_range("one\n", Some(5), None).is_err());
+        assert!(format_line_range("one\n", Some(usize::MAX), None).is_err());
+        assert!(format_line_range("", Some(usize::MAX), None).is_ok());
+    }
+
+    #[test]
+    fn tree_lists_directories_once() {
+        let tmp = sample_crate();
+        expect_test::expect![[r#"
+            Cargo.toml
+            src/
+              de/
+                mod.rs
+              lib.rs
+        "#]]
+        .assert_eq(&format_tree(tmp.path()));
+    }
+}
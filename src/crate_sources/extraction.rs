@@ -8,6 +8,8 @@ use anyhow::{Context, Result};
 use flate2::read::GzDecoder;
 use tar::Archive;
 
+use crate::progress;
+
 /// Handles extraction of .crate files to local cache
 pub struct CrateExtractor;
 
@@ -24,11 +26,16 @@ impl CrateExtractor {
     ) -> Result<PathBuf> {
         let file = fs::File::open(crate_path)
             .with_context(|| format!("failed to open {}", crate_path.display()))?;
+        progress::report(format!("Extracting {}", crate_path.display()));
         self.extract_from_reader(file, extraction_path)?;
         Ok(extraction_path.to_path_buf())
     }
 
     /// Download a crate from crates.io and extract it
+    ///
+    /// The body is read in chunks so download progress can be reported, and
+    /// nothing is written to disk until it is complete: dropping this future
+    /// (e.g. when an MCP request is cancelled) aborts the download cleanly.
     pub async fn download_and_extract_crate(
         &self,
         crate_name: &str,
@@ -38,7 +45,8 @@ impl CrateExtractor {
         let download_url =
             format!("https://static.crates.io/crates/{crate_name}/{crate_name}-{version}.crate",);
 
-        let response = reqwest::get(&download_url)
+        progress::report(format!("Downloading {crate_name} v{version}"));
+        let mut response = reqwest::get(&download_url)
             .await
             .with_context(|| format!("failed to download {crate_name} v{version}"))?;
 
@@ -49,24 +57,65 @@ impl CrateExtractor {
             );
         }
 
-        let bytes = response.bytes().await?;
+        let total = response.content_length();
+        let mut bytes = Vec::with_capacity(total.unwrap_or(0) as usize);
+        let mut reported_step = 0;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .with_context(|| format!("failed to download {crate_name} v{version}"))?
+        {
+            bytes.extend_from_slice(&chunk);
+            if let Some(total) = total.filter(|t| *t > 0) {
+                // Report every 10% of the download.
+                let step = bytes.len() as u64 * 10 / total;
+                if step > reported_step && step < 10 {
+                    reported_step = step;
+                    progress::report(format!(
+                        "Downloading {crate_name} v{version}: {}% of {} KiB",
+                        step * 10,
+                        total / 1024
+                    ));
+                }
+            }
+        }
+
+        progress::report(format!("Extracting {crate_name} v{version}"));
         self.extract_from_reader(std::io::Cursor::new(bytes), extraction_path)?;
         Ok(extraction_path.to_path_buf())
     }
 
     /// Extract from any reader to the specified directory
+    ///
+    /// Extracts into a temporary sibling directory and renames it into place,
+    /// so a failed extraction never leaves a partial crate that later lookups
+    /// would mistake for a cached one.
     fn extract_from_reader<R: Read>(&self, reader: R, extraction_path: &Path) -> Result<()> {
-        fs::create_dir_all(extraction_path)?;
+        let parent = extraction_path
+            .parent()
+            .context("extraction path has no parent directory")?;
+        fs::create_dir_all(parent)?;
+        let temp_dir = tempfile::tempdir_in(parent)
+            .with_context(|| format!("failed to create temp directory in {}", parent.display()))?;
 
         let gz_decoder = GzDecoder::new(reader);
         let mut archive = Archive::new(gz_decoder);
 
         archive
-            .unpack(extraction_path)
+            .unpack(temp_dir.path())
             .context("failed to extract crate archive")?;
 
         // Flatten: .crate archives contain a single top-level directory (name-version/)
-        self.flatten_extraction(extraction_path)?;
+        self.flatten_extraction(temp_dir.path())?;
+
+        if let Err(e) = fs::rename(temp_dir.path(), extraction_path) {
+            // Another process may have extracted the same crate concurrently.
+            if !extraction_path.exists() {
+                return Err(e).with_context(|| {
+                    format!("failed to move extraction to {}", extraction_path.display())
+                });
+            }
+        }
 
         Ok(())
     }
@@ -117,3 +166,67 @@ impl CrateExtractor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    /// Build a `.crate` archive with a single `name-version/` top-level directory.
+    fn write_crate_archive(path: &Path) {
+        let encoder = GzEncoder::new(fs::File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in [
+            ("demo-0.1.0/Cargo.toml", "[package]\n"),
+            ("demo-0.1.0/src/lib.rs", "pub fn demo() {}\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn extraction_is_flattened_and_moved_into_place() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("demo-0.1.0.crate");
+        write_crate_archive(&archive);
+
+        let cache = tmp.path().join("extractions");
+        let dest = cache.join("demo-0.1.0");
+        let path = CrateExtractor::new()
+            .extract_crate_to_cache(&archive, &dest)
+            .await
+            .unwrap();
+
+        assert_eq!(path, dest);
+        assert!(dest.join("Cargo.toml").is_file());
+        assert!(dest.join("src/lib.rs").is_file());
+        // No temporary directories are left behind.
+        let entries: Vec<_> = fs::read_dir(&cache).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_extraction_leaves_no_cache_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("broken.crate");
+        fs::write(&archive, "not a gzip archive").unwrap();
+
+        let cache = tmp.path().join("extractions");
+        let dest = cache.join("broken-0.1.0");
+        let result = CrateExtractor::new()
+            .extract_crate_to_cache(&archive, &dest)
+            .await;
+
+        assert!(result.is_err());
+        assert!(!dest.exists());
+        assert_eq!(fs::read_dir(&cache).unwrap().count(), 0);
+    }
+}
@@ -1,5 +1,6 @@
 //! List workspace crates with available guidance
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::Result;
@@ -21,6 +22,42 @@ pub fn workspace_semver_pairs(cwd: &Path) -> Vec<(String, semver::Version)> {
         .collect()
 }
 
+/// Facts about a workspace beyond its dependency versions, used when
+/// rendering templated skills.
+#[derive(Debug, Default)]
+pub struct WorkspaceDetails {
+    /// Edition of the root package, or of the first workspace member.
+    pub edition: Option<String>,
+    /// Enabled features of each crate in the resolved dependency graph.
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// Load [`WorkspaceDetails`] with the features cargo enables for a default
+/// build. Returns empty details on failure.
+pub fn workspace_details(cwd: &Path) -> WorkspaceDetails {
+    load_workspace_details(cwd).unwrap_or_default()
+}
+
+fn load_workspace_details(cwd: &Path) -> Result<WorkspaceDetails> {
+    let metadata = MetadataCommand::new().current_dir(cwd).exec()?;
+
+    let edition = metadata
+        .root_package()
+        .or_else(|| metadata.workspace_packages().first().copied())
+        .map(|p| p.edition.to_string());
+
+    let mut features = BTreeMap::new();
+    if let Some(resolve) = &metadata.resolve {
+        for node in &resolve.nodes {
+            features
+                .entry(metadata[&node.id].name.to_string())
+                .or_insert_with(|| node.features.clone());
+        }
+    }
+
+    Ok(WorkspaceDetails { edition, features })
+}
+
 /// List all crates in the workspace's resolved dependency graph.
 ///
 /// Eventually this will also indicate which crates have specialized
@@ -4,12 +4,20 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 
+use crate::progress;
+
+mod api;
+mod browse;
 mod cache;
 mod extraction;
 mod list;
+mod search;
 mod version;
 
-pub use list::workspace_semver_pairs;
+pub use api::api_output;
+pub use browse::{read_output, tree_output};
+pub use list::{WorkspaceDetails, workspace_details, workspace_semver_pairs};
+pub use search::{SearchOptions, search_output};
 
 /// Result of fetching a crate's sources
 #[derive(Debug, Clone)]
@@ -46,12 +54,19 @@ impl<'a> RustCrateFetch<'a> {
     }
 
     /// Fetch the crate sources, returning the path to extracted sources
+    ///
+    /// Each stage (version resolution, cache lookup, download, extraction) is
+    /// reported through [`crate::progress`].
     pub async fn fetch(self) -> Result<FetchResult> {
+        progress::report(format!("Resolving version of {}", self.crate_name));
         let resolver = version::VersionResolver::new(self.workspace);
         let (canonical_name, version) = resolver
             .resolve(&self.crate_name, self.version_spec.as_deref())
             .await?;
 
+        progress::report(format!(
+            "Looking up {canonical_name} v{version} in local caches"
+        ));
         let cache_manager = cache::CacheManager::new()?;
         let extractor = extraction::CrateExtractor::new();
 
@@ -66,3 +81,18 @@ impl<'a> RustCrateFetch<'a> {
         })
     }
 }
+
+/// Fetch a crate with an optional version constraint.
+///
+/// Shorthand for the common `RustCrateFetch` setup used by the crate tools.
+pub(crate) async fn fetch_crate(
+    name: &str,
+    version: Option<&str>,
+    workspace: &[(String, semver::Version)],
+) -> Result<FetchResult> {
+    let mut fetch = RustCrateFetch::new(name, workspace);
+    if let Some(v) = version {
+        fetch = fetch.version(v);
+    }
+    fetch.fetch().await
+}
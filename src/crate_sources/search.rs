@@ -0,0 +1,320 @@
+//! Regex search over extracted crate sources
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Default number of matches returned by a search.
+const DEFAULT_LIMIT: usize = 50;
+
+/// Default number of context lines shown around each match.
+const DEFAULT_CONTEXT: usize = 2;
+
+/// Options controlling a crate source search.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Maximum number of matching lines to return.
+    pub limit: usize,
+    /// Number of lines of context to show before and after each match.
+    pub context: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+            context: DEFAULT_CONTEXT,
+        }
+    }
+}
+
+/// A single matching line, with surrounding context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// Path of the file, relative to the crate root.
+    pub path: PathBuf,
+    /// 1-based line number of the match.
+    pub line: usize,
+    /// Lines shown for this match as `(line number, text)`, including context.
+    pub lines: Vec<(usize, String)>,
+}
+
+/// Results of searching a crate's sources.
+#[derive(Debug, Default)]
+pub struct SearchResults {
+    pub matches: Vec<SearchMatch>,
+    /// True if the search stopped early because `limit` was reached.
+    pub truncated: bool,
+}
+
+/// Fetch a crate's sources and format regex search results as display text.
+pub async fn search_output(
+    name: &str,
+    version: Option<&str>,
+    pattern: &str,
+    options: &SearchOptions,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    // Validate the pattern before fetching so bad input fails fast.
+    let regex = Regex::new(pattern).with_context(|| format!("invalid regex: {pattern}"))?;
+
+    let result = super::fetch_crate(name, version, workspace).await?;
+
+    let results = search_dir(&result.path, &regex, options);
+
+    let mut output = format!(
+        "Crate: {}\nVersion: {}\nSource: {}\nPattern: {pattern}\n",
+        result.name,
+        result.version,
+        result.path.display()
+    );
+    output.push_str(&format_results(&results, options));
+    Ok(output)
+}
+
+/// Search all text files under `root` for lines matching `regex`.
+///
+/// Files are visited in sorted order so results are deterministic.
+/// Stops after `options.limit` matches.
+pub fn search_dir(root: &Path, regex: &Regex, options: &SearchOptions) -> SearchResults {
+    let mut results = SearchResults::default();
+
+    for path in source_files(root) {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if content.contains('\0') {
+            continue;
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        for (idx, line) in lines.iter().enumerate() {
+            if !regex.is_match(line) {
+                continue;
+            }
+            if results.matches.len() == options.limit {
+                results.truncated = true;
+                return results;
+            }
+
+            let start = idx.saturating_sub(options.context);
+            let end = idx
+                .saturating_add(options.context)
+                .saturating_add(1)
+                .min(lines.len());
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            results.matches.push(SearchMatch {
+                path: relative,
+                line: idx + 1,
+                lines: (start..end)
+                    .map(|i| (i + 1, lines[i].to_string()))
+                    .collect(),
+            });
+        }
+    }
+
+    results
+}
+
+/// Format search results grouped by file, in the style of `grep -n`:
+/// matching lines use `:` after the line number, context lines use `-`.
+fn format_results(results: &SearchResults, options: &SearchOptions) -> String {
+    if results.matches.is_empty() {
+        return "\nNo matches found.\n".to_string();
+    }
+
+    let mut out = String::new();
+    let mut current_file: Option<&Path> = None;
+    let mut last_line = 0;
+
+    for m in &results.matches {
+        if current_file != Some(m.path.as_path()) {
+            out.push_str(&format!("\n{}\n", m.path.display()));
+            current_file = Some(&m.path);
+            last_line = 0;
+        } else if m.lines.first().is_some_and(|(n, _)| *n > last_line + 1) {
+            out.push_str("--\n");
+        }
+
+        for (number, text) in &m.lines {
+            // Skip lines already printed as part of an overlapping match.
+            if *number <= last_line {
+                continue;
+            }
+            let sep = if *number == m.line { ':' } else { '-' };
+            out.push_str(&format!("{number}{sep}{text}\n"));
+            last_line = *number;
+        }
+    }
+
+    if results.truncated {
+        out.push_str(&format!(
+            "\nShowing the first {} matches; more were found. \
+             Narrow the pattern or raise the limit to see more.\n",
+            options.limit
+        ));
+    } else {
+        out.push_str(&format!("\n{} match(es).\n", results.matches.len()));
+    }
+
+    out
+}
+
+/// Collect all files under a crate root, sorted for deterministic output.
+///
+/// Skips hidden entries (e.g. `.git`, `.cargo_vcs_info.json`), `target/`,
+/// and symlinks.
+pub(crate) fn source_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_files_recursive(root, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_files_recursive(dir: &Path, out: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+        // Symlinks are skipped: they could point outside the crate, or
+        // form a cycle.
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_files_recursive(&path, out);
+        } else if file_type.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::fs;
+
+    fn sample_crate() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("lib.rs"),
+            indoc! {"
+                pub mod de;
+
+                pub fn to_string() {}
+
+                pub fn from_str() {}
+            "},
+        )
+        .unwrap();
+        fs::write(
+            src.join("de.rs"),
+            indoc! {"
+                pub trait Deserialize {}
+
+                pub fn from_slice() {}
+            "},
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.path().join(".git")).unwrap();
+        fs::write(
+            tmp.path().join(".git").join("from_hidden"),
+            "pub fn from_x() {}",
+        )
+        .unwrap();
+        tmp
+    }
+
+    #[test]
+    fn search_finds_matches_in_sorted_files() {
+        let tmp = sample_crate();
+        let regex = Regex::new(r"fn from_").unwrap();
+        let results = search_dir(tmp.path(), &regex, &SearchOptions::default());
+
+        let found: Vec<_> = results
+            .matches
+            .iter()
+            .map(|m| (m.path.display().to_string(), m.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("src/de.rs".to_string(), 3), ("src/lib.rs".to_string(), 5)]
+        );
+        assert!(!results.truncated);
+    }
+
+    #[test]
+    fn search_respects_limit() {
+        let tmp = sample_crate();
+        let regex = Regex::new(r"pub").unwrap();
+        let options = SearchOptions {
+            limit: 2,
+            context: 0,
+        };
+        let results = search_dir(tmp.path(), &regex, &options);
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn search_with_huge_context_shows_whole_file() {
+        let tmp = sample_crate();
+        let regex = Regex::new(r"fn from_slice").unwrap();
+        let options = SearchOptions {
+            limit: 1,
+            context: usize::MAX,
+        };
+        let results = search_dir(tmp.path(), &regex, &options);
+        let lines: Vec<_> = results.matches[0].lines.iter().map(|(n, _)| *n).collect();
+        assert_eq!(lines, [1, 2, 3]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn source_files_skip_symlinks() {
+        let tmp = sample_crate();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret.rs"), "pub fn secret() {}\n").unwrap();
+        std::os::unix::fs::symlink(outside.path(), tmp.path().join("src/outside")).unwrap();
+        std::os::unix::fs::symlink(tmp.path(), tmp.path().join("src/cycle")).unwrap();
+
+        let files: Vec<_> = source_files(tmp.path())
+            .into_iter()
+            .map(|path| path.strip_prefix(tmp.path()).unwrap().display().to_string())
+            .collect();
+        assert_eq!(files, ["src/de.rs", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn format_results_with_context() {
+        let tmp = sample_crate();
+        let regex = Regex::new(r"fn (to_string|from_str)").unwrap();
+        let options = SearchOptions {
+            limit: 10,
+            context: 1,
+        };
+        let results = search_dir(tmp.path(), &regex, &options);
+        expect_test::expect![[r#"
+
+            src/lib.rs
+            2-
+            3:pub fn to_string() {}
+            4-
+            5:pub fn from_str() {}
+
+            2 match(es).
+        "#]]
+        .assert_eq(&format_results(&results, &options));
+    }
+}
@@ -0,0 +1,358 @@
+//! Compiler diagnostics from `cargo --message-format=json`, and the
+//! dependency crates they involve.
+//!
+//! A diagnostic involves a crate when one of its spans points into that
+//! crate's registry sources, or when its messages or labels mention a path
+//! rooted at the crate (`serde::de::Deserialize`, `tokio::spawn`). Only crates
+//! in the workspace's dependency graph are reported.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::Message;
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
+use regex::Regex;
+
+use crate::plugins::PluginRegistry;
+use crate::progress;
+use crate::skills;
+
+/// How many diagnostics `diagnose_output` lists before summarizing the rest.
+const MAX_LISTED: usize = 20;
+
+/// `<name>-<version>` directories under `~/.cargo/registry/src/<index>/`.
+static REGISTRY_SOURCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"registry[/\\]src[/\\][^/\\]+[/\\]([A-Za-z0-9_-]+?)-\d+\.\d+\.\d+[^/\\]*[/\\]")
+        .unwrap()
+});
+
+/// The first segment of a path like `serde::de::Deserialize`.
+static PATH_ROOT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b([A-Za-z_][A-Za-z0-9_]*)::").unwrap());
+
+/// Extract the compiler diagnostics from cargo's JSON output, dropping
+/// duplicates (the same diagnostic is often reported once per target) and
+/// rustc's closing remarks ("aborting due to ...", "For more information ...").
+pub fn parse_compiler_messages(json: &str) -> Vec<Diagnostic> {
+    let mut seen = HashSet::new();
+    Message::parse_stream(json.as_bytes())
+        .filter_map(|message| match message {
+            Ok(Message::CompilerMessage(message)) => Some(message.message),
+            _ => None,
+        })
+        .filter(|diagnostic| {
+            diagnostic.level != DiagnosticLevel::FailureNote
+                && !diagnostic.message.starts_with("aborting due to")
+        })
+        .filter(|diagnostic| seen.insert(diagnostic.rendered.clone()))
+        .collect()
+}
+
+/// Whether a diagnostic is an error (including internal compiler errors).
+pub fn is_error(diagnostic: &Diagnostic) -> bool {
+    matches!(
+        diagnostic.level,
+        DiagnosticLevel::Error | DiagnosticLevel::Ice
+    )
+}
+
+/// One-line description, e.g. `error[E0277]: the trait bound ... is not satisfied`.
+pub fn headline(diagnostic: &Diagnostic) -> String {
+    let level = match diagnostic.level {
+        DiagnosticLevel::Ice => "internal compiler error",
+        DiagnosticLevel::Error => "error",
+        DiagnosticLevel::Warning => "warning",
+        DiagnosticLevel::Help => "help",
+        _ => "note",
+    };
+    match &diagnostic.code {
+        Some(code) => format!("{level}[{}]: {}", code.code, diagnostic.message),
+        None => format!("{level}: {}", diagnostic.message),
+    }
+}
+
+/// `file:line:column` of the diagnostic's primary span.
+pub fn primary_location(diagnostic: &Diagnostic) -> Option<String> {
+    diagnostic
+        .spans
+        .iter()
+        .find(|span| span.is_primary)
+        .map(|span| {
+            format!(
+                "{}:{}:{}",
+                span.file_name, span.line_start, span.column_start
+            )
+        })
+}
+
+/// Dependency crates (from `workspace`) that a diagnostic involves.
+pub fn involved_crates(
+    diagnostic: &Diagnostic,
+    workspace: &[(String, semver::Version)],
+) -> BTreeSet<String> {
+    let mut candidates = BTreeSet::new();
+    collect_candidates(diagnostic, &mut candidates);
+
+    workspace
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| candidates.contains(&name.replace('-', "_")))
+        .cloned()
+        .collect()
+}
+
+/// Collect crate names (with `_` separators) mentioned by a diagnostic and
+/// its children.
+fn collect_candidates(diagnostic: &Diagnostic, out: &mut BTreeSet<String>) {
+    collect_path_roots(&diagnostic.message, out);
+    for span in &diagnostic.spans {
+        collect_span_candidates(span, out);
+    }
+    for child in &diagnostic.children {
+        collect_candidates(child, out);
+    }
+}
+
+fn collect_span_candidates(span: &DiagnosticSpan, out: &mut BTreeSet<String>) {
+    if let Some(captures) = REGISTRY_SOURCE.captures(&span.file_name) {
+        out.insert(captures[1].replace('-', "_"));
+    }
+    if let Some(label) = &span.label {
+        collect_path_roots(label, out);
+    }
+    if let Some(expansion) = &span.expansion {
+        collect_span_candidates(&expansion.span, out);
+    }
+}
+
+fn collect_path_roots(text: &str, out: &mut BTreeSet<String>) {
+    for captures in PATH_ROOT.captures_iter(text) {
+        out.insert(captures[1].to_string());
+    }
+}
+
+/// Run `cargo check --message-format=json` in `root`, returning its JSON output.
+pub async fn run_cargo_check(root: &Path, args: &[String]) -> Result<String> {
+    progress::report("Running cargo check");
+    let output = tokio::process::Command::new("cargo")
+        .arg("check")
+        .arg("--message-format=json")
+        .args(args)
+        .current_dir(root)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .context("failed to run `cargo check`")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    // A failing build still produces JSON; only a cargo-level failure (bad
+    // manifest, unknown flag) leaves nothing to report.
+    if !output.status.success() && parse_compiler_messages(&stdout).is_empty() {
+        bail!(
+            "cargo check failed:\n{}",
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(stdout)
+}
+
+/// Summarize the diagnostics in cargo's JSON output and attach guidance for
+/// the dependency crates they involve.
+pub async fn diagnose_output(
+    json: &str,
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> String {
+    let diagnostics = parse_compiler_messages(json);
+    if diagnostics.is_empty() {
+        return "No compiler diagnostics.\n".to_string();
+    }
+
+    let errors = diagnostics.iter().filter(|d| is_error(d)).count();
+    let others = diagnostics.len() - errors;
+    let mut out = format!("{errors} error(s), {others} warning(s) or note(s)\n");
+
+    let mut all_crates = BTreeSet::new();
+    for diagnostic in diagnostics.iter().take(MAX_LISTED) {
+        out.push_str(&format!("\n{}\n", headline(diagnostic)));
+        if let Some(location) = primary_location(diagnostic) {
+            out.push_str(&format!("  --> {location}\n"));
+        }
+        let crates = involved_crates(diagnostic, workspace);
+        if !crates.is_empty() {
+            let names: Vec<_> = crates.iter().map(String::as_str).collect();
+            out.push_str(&format!("  involves: {}\n", names.join(", ")));
+        }
+        all_crates.extend(crates);
+    }
+    if diagnostics.len() > MAX_LISTED {
+        out.push_str(&format!(
+            "\n... and {} more\n",
+            diagnostics.len() - MAX_LISTED
+        ));
+    }
+
+    for name in &all_crates {
+        let advice = skills::guidance(name, None, registry, root, workspace).await;
+        if !advice.is_empty() {
+            out.push_str(&format!("\n# Guidance for `{name}`\n"));
+            out.push_str(&advice.format_output(&skills::GuidanceOptions::configured()));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> Vec<(String, semver::Version)> {
+        ["serde", "serde_json", "tokio", "my-app"]
+            .into_iter()
+            .map(|name| (name.to_string(), semver::Version::new(1, 0, 0)))
+            .collect()
+    }
+
+    /// A `compiler-message` line as cargo prints it.
+    fn compiler_message(diagnostic: serde_json::Value) -> String {
+        serde_json::json!({
+            "reason": "compiler-message",
+            "package_id": "my-app 0.1.0 (path+file:///work/my-app)",
+            "manifest_path": "/work/my-app/Cargo.toml",
+            "target": {
+                "kind": ["bin"], "crate_types": ["bin"], "name": "my-app",
+                "src_path": "/work/my-app/src/main.rs", "edition": "2021",
+                "doc": true, "doctest": false, "test": true
+            },
+            "message": diagnostic,
+        })
+        .to_string()
+    }
+
+    fn span(file_name: &str, label: Option<&str>, is_primary: bool) -> serde_json::Value {
+        serde_json::json!({
+            "file_name": file_name, "byte_start": 0, "byte_end": 1,
+            "line_start": 12, "line_end": 12, "column_start": 5, "column_end": 9,
+            "is_primary": is_primary, "text": [], "label": label,
+            "suggested_replacement": null, "suggestion_applicability": null,
+            "expansion": null
+        })
+    }
+
+    fn diagnostic(
+        level: &str,
+        code: Option<&str>,
+        message: &str,
+        spans: Vec<serde_json::Value>,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "message": message,
+            "code": code.map(|code| serde_json::json!({ "code": code, "explanation": null })),
+            "level": level,
+            "spans": spans,
+            "children": [],
+            "rendered": format!("{level}: {message}\n"),
+        })
+    }
+
+    fn sample_output() -> String {
+        let trait_bound = diagnostic(
+            "error",
+            Some("E0277"),
+            "the trait bound `Config: serde::de::Deserialize<'de>` is not satisfied",
+            vec![
+                span("src/main.rs", Some("required by a bound"), true),
+                span(
+                    "/home/u/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde_json-1.0.140/src/de.rs",
+                    None,
+                    false,
+                ),
+            ],
+        );
+        let unused = diagnostic(
+            "warning",
+            None,
+            "unused variable: `x`",
+            vec![span("src/main.rs", None, true)],
+        );
+        [
+            r#"{"reason":"compiler-artifact","package_id":"x","manifest_path":"/x","target":{"kind":["lib"],"crate_types":["lib"],"name":"x","src_path":"/x","edition":"2021","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":0,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":[],"executable":null,"fresh":true}"#.to_string(),
+            compiler_message(trait_bound.clone()),
+            compiler_message(trait_bound),
+            compiler_message(unused),
+            compiler_message(diagnostic(
+                "error",
+                None,
+                "aborting due to 1 previous error",
+                vec![],
+            )),
+            r#"{"reason":"build-finished","success":false}"#.to_string(),
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn parses_and_dedupes_compiler_messages() {
+        let diagnostics = parse_compiler_messages(&sample_output());
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            headline(&diagnostics[0]),
+            "error[E0277]: the trait bound `Config: serde::de::Deserialize<'de>` is not satisfied"
+        );
+        assert_eq!(
+            primary_location(&diagnostics[0]).as_deref(),
+            Some("src/main.rs:12:5")
+        );
+        assert!(is_error(&diagnostics[0]));
+        assert!(!is_error(&diagnostics[1]));
+    }
+
+    #[test]
+    fn finds_crates_from_paths_and_registry_spans() {
+        let diagnostics = parse_compiler_messages(&sample_output());
+        let crates = involved_crates(&diagnostics[0], &workspace());
+        assert_eq!(
+            crates.into_iter().collect::<Vec<_>>(),
+            vec!["serde", "serde_json"]
+        );
+        assert!(involved_crates(&diagnostics[1], &workspace()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn diagnose_summarizes_and_attaches_guidance() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_md = tmp.path().join("SKILL.md");
+        std::fs::write(
+            &skill_md,
+            indoc::indoc! {"
+                ---
+                name: serde-derive
+                crates: serde
+                activation: default
+                ---
+
+                Derive `Deserialize` on config types.
+            "},
+        )
+        .unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![crate::plugins::StandaloneSkill {
+                source: "test".to_string(),
+                precedence: Default::default(),
+                skill: skills::load_standalone_skill(&skill_md, tmp.path()).unwrap(),
+            }],
+        };
+
+        let output = diagnose_output(&sample_output(), &registry, tmp.path(), &workspace()).await;
+        assert!(output.starts_with("1 error(s), 1 warning(s) or note(s)\n"));
+        assert!(output.contains("  --> src/main.rs:12:5\n  involves: serde, serde_json\n"));
+        assert!(output.contains("# Guidance for `serde`"));
+        assert!(output.contains("Derive `Deserialize` on config types."));
+        assert!(!output.contains("# Guidance for `serde_json`"));
+    }
+}
@@ -4,6 +4,8 @@ use std::path::PathBuf;
 
 use anyhow::{Context, Result, bail};
 
+use crate::progress;
+
 /// Controls how aggressively plugin sources are updated.
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 pub enum UpdateLevel {
@@ -252,6 +254,7 @@ impl PluginCacheManager {
                 }
 
                 // None (past debounce) or Check: check freshness via API
+                progress::report(format!("Checking {source_url} for updates"));
                 match self.client.resolve_commit_sha(source).await {
                     Ok(remote_sha) => {
                         if meta.commit_sha == remote_sha {
@@ -298,8 +301,11 @@ impl PluginCacheManager {
         meta_path: &std::path::Path,
         sha: &str,
     ) -> Result<()> {
+        progress::report(format!("Downloading {source_url}"));
         let tarball = self.client.download_tarball(source).await?;
 
+        progress::report(format!("Extracting {source_url}"));
+
         // Extract to a temp directory first, then move into place
         std::fs::create_dir_all(&self.cache_dir).with_context(|| {
             format!(
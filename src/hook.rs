@@ -1,9 +1,19 @@
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitCode, Stdio};
+use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
 
 use crate::plugins::ParsedPlugin;
+use crate::skills::ToolUse;
+
+/// Files larger than this are not searched for skill trigger patterns.
+const MAX_TRIGGER_FILE_BYTES: u64 = 1024 * 1024;
+
+/// How long the records of a session's shown skills, or of a workspace's
+/// skills with triggers, are kept after they were last written.
+const RECORD_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
 #[derive(Debug, Clone, clap::ValueEnum, Serialize, Deserialize, PartialEq, Eq)]
 pub enum HookEvent {
@@ -51,6 +61,60 @@ pub struct PreToolUsePayload {
     pub tool_name: String,
 }
 
+/// The result of running one plugin hook.
+#[derive(Debug, Clone, Default)]
+pub struct HookOutput {
+    /// What the hook printed to stdout.
+    pub stdout: String,
+    /// Whether the hook exited with status 2, which blocks the tool call.
+    pub blocked: bool,
+}
+
+/// A hook's verdict on a tool call, ordered from least to most restrictive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionDecision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl PermissionDecision {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "allow" => Some(Self::Allow),
+            "ask" => Some(Self::Ask),
+            "deny" => Some(Self::Deny),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Ask => "ask",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+/// The most restrictive decision among hook outputs: `deny` if a hook
+/// exited with status 2, otherwise the strictest `permissionDecision` in
+/// the JSON the hooks printed.
+pub fn permission_decision(outputs: &[HookOutput]) -> Option<PermissionDecision> {
+    if outputs.iter().any(|output| output.blocked) {
+        return Some(PermissionDecision::Deny);
+    }
+    outputs
+        .iter()
+        .filter_map(|output| serde_json::from_str::<serde_json::Value>(&output.stdout).ok())
+        .filter_map(|json| {
+            json.pointer("/hookSpecificOutput/permissionDecision")?
+                .as_str()
+                .and_then(PermissionDecision::parse)
+        })
+        .max()
+}
+
 pub async fn run(event: HookEvent) -> ExitCode {
     let mut input = String::new();
     if let Err(e) = std::io::stdin().read_to_string(&mut input) {
@@ -73,24 +137,117 @@ pub async fn run(event: HookEvent) -> ExitCode {
         return ExitCode::FAILURE;
     }
 
-    dispatch_hook(payload).await
+    let context = triggered_guidance(&payload).await;
+    let outputs = dispatch_hook(payload).await;
+    if let Some(output) = merge_outputs(&outputs, context) {
+        println!("{output}");
+    }
+    if outputs.iter().any(|output| output.blocked) {
+        ExitCode::from(2)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Combine what the plugin hooks printed, and any triggered skill guidance,
+/// into one hook output: the agent reads stdout as a single JSON document.
+///
+/// Top-level fields come from the first hook that sets them, except that
+/// `continue: false` from any hook wins. In `hookSpecificOutput`, the
+/// strictest `permissionDecision` wins along with its reason, every
+/// `additionalContext` is kept, and other fields come from the first hook.
+/// Output that is not a JSON object is plain text for the agent, and is
+/// passed on as `additionalContext`.
+fn merge_outputs(outputs: &[HookOutput], context: Option<String>) -> Option<serde_json::Value> {
+    use serde_json::Value;
+
+    let mut merged = serde_json::Map::new();
+    let mut specific = serde_json::Map::new();
+    let mut contexts = Vec::new();
+    let mut decision: Option<(PermissionDecision, Option<Value>)> = None;
+    for output in outputs {
+        let stdout = output.stdout.trim();
+        if stdout.is_empty() {
+            continue;
+        }
+        let Ok(Value::Object(fields)) = serde_json::from_str(stdout) else {
+            contexts.push(stdout.to_string());
+            continue;
+        };
+        for (key, value) in fields {
+            match (key.as_str(), value) {
+                ("hookSpecificOutput", Value::Object(hook_fields)) => {
+                    let reason = hook_fields.get("permissionDecisionReason").cloned();
+                    for (key, value) in hook_fields {
+                        match key.as_str() {
+                            "additionalContext" => {
+                                contexts.extend(value.as_str().map(str::to_string));
+                            }
+                            "permissionDecision" => {
+                                if let Some(new) =
+                                    value.as_str().and_then(PermissionDecision::parse)
+                                    && decision.as_ref().is_none_or(|(top, _)| new > *top)
+                                {
+                                    decision = Some((new, reason.clone()));
+                                }
+                            }
+                            "permissionDecisionReason" | "hookEventName" => {}
+                            _ => {
+                                specific.entry(key).or_insert(value);
+                            }
+                        }
+                    }
+                }
+                ("continue", Value::Bool(false)) => {
+                    merged.insert(key, Value::Bool(false));
+                }
+                (_, value) => {
+                    merged.entry(key).or_insert(value);
+                }
+            }
+        }
+    }
+
+    contexts.extend(context);
+    if let Some((decision, reason)) = decision {
+        specific.insert("permissionDecision".to_string(), decision.as_str().into());
+        if let Some(reason) = reason {
+            specific.insert("permissionDecisionReason".to_string(), reason);
+        }
+    }
+    if !contexts.is_empty() {
+        specific.insert(
+            "additionalContext".to_string(),
+            contexts.join("\n\n").into(),
+        );
+    }
+    if !specific.is_empty() {
+        specific.insert("hookEventName".to_string(), "PreToolUse".into());
+        merged.insert("hookSpecificOutput".to_string(), Value::Object(specific));
+    }
+    (!merged.is_empty()).then_some(Value::Object(merged))
 }
 
 /// Handle hook dispatch for a parsed payload string. Separated from `run`
 /// so tests and other callers can invoke it without wiring stdin.
-pub async fn dispatch_hook(payload: HookPayload) -> ExitCode {
+///
+/// Each hook's stdout is captured and returned rather than inherited, so
+/// callers decide where it goes: `run` prints it, and the ACP proxy (whose
+/// stdout is the protocol stream) only logs it.
+pub async fn dispatch_hook(payload: HookPayload) -> Vec<HookOutput> {
     tracing::info!(?payload, "hook invoked");
 
     let plugins = crate::plugins::load_all_plugins();
     let hooks = hooks_for_payload(&plugins, &payload);
 
+    let mut outputs = Vec::new();
     for (plugin_name, hook) in hooks {
         tracing::info!(?plugin_name, hook = %hook.name, cmd = %hook.command, "running plugin hook");
         let spawn_res = Command::new("sh")
             .arg("-c")
             .arg(&hook.command)
             .stdin(Stdio::piped())
-            .stdout(Stdio::inherit())
+            .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
             .spawn();
 
@@ -104,8 +261,15 @@ pub async fn dispatch_hook(payload: HookPayload) -> ExitCode {
                     }
                 }
 
-                match child.wait() {
-                    Ok(status) => tracing::info!(?status, "hook finished"),
+                match child.wait_with_output() {
+                    Ok(output) => {
+                        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+                        tracing::info!(status = ?output.status, %stdout, "hook finished");
+                        outputs.push(HookOutput {
+                            stdout,
+                            blocked: output.status.code() == Some(2),
+                        });
+                    }
                     Err(e) => tracing::warn!(error = %e, "failed waiting for hook process"),
                 }
             }
@@ -113,7 +277,276 @@ pub async fn dispatch_hook(payload: HookPayload) -> ExitCode {
         }
     }
 
-    ExitCode::SUCCESS
+    outputs
+}
+
+/// Guidance from skills with `activation: triggers` that match the tool call
+/// in `payload`.
+///
+/// Each skill is shown once per session: the skills already shown are
+/// recorded under the cache directory, by `session_id`. What is needed to
+/// find the skills with triggers is recorded by workspace (see
+/// [`TriggerRecord`]), so that later tool calls only redo the parts whose
+/// inputs changed, and skip loading skills altogether when the workspace has
+/// none with triggers.
+async fn triggered_guidance(payload: &HookPayload) -> Option<String> {
+    let HookSubPayload::PreToolUse(pre_tool_use) = &payload.sub_payload;
+    let root = match payload.rest.get("cwd").and_then(|cwd| cwd.as_str()) {
+        Some(cwd) => PathBuf::from(cwd),
+        None => std::env::current_dir().ok()?,
+    };
+
+    let cache_dir = crate::config::cache_dir();
+    let record_file = trigger_record_file(&cache_dir, &root);
+    let previous = TriggerRecord::read(&record_file, &root);
+    let mut record = TriggerRecord::current(&root, previous.as_ref());
+    let candidates = match &previous {
+        Some(previous) if previous.same_skills(&record) && !previous.has_triggers => Vec::new(),
+        _ => {
+            let registry = crate::plugins::load_registry();
+            let candidates = crate::skills::trigger_skills(&registry, &record.workspace()).await;
+            record.has_triggers = !candidates.is_empty();
+            candidates
+        }
+    };
+    if previous.as_ref() != Some(&record) {
+        record.write(&record_file);
+    }
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let tool_use = tool_use(
+        pre_tool_use,
+        payload.rest.get("tool_input").unwrap_or_default(),
+        &root,
+    );
+    let shown_file = shown_skills_file(&cache_dir, payload);
+    let shown: Vec<PathBuf> = shown_file
+        .as_ref()
+        .and_then(|file| std::fs::read_to_string(file).ok())
+        .map(|shown| shown.lines().map(PathBuf::from).collect())
+        .unwrap_or_default();
+    let workspace = record.workspace();
+    let (output, paths) =
+        crate::skills::triggered_output(&tool_use, &shown, &candidates, &root, &workspace).await;
+    if paths.is_empty() {
+        return None;
+    }
+    tracing::info!(tool = %tool_use.tool, file = ?tool_use.file, skills = ?paths, "skills triggered");
+
+    if let Some(file) = shown_file {
+        let mut record = String::new();
+        for path in &paths {
+            record.push_str(&format!("{}\n", path.display()));
+        }
+        let dir = file.parent().unwrap_or(&file);
+        if !file.exists() {
+            remove_expired(dir);
+        }
+        let written = std::fs::create_dir_all(dir).and_then(|()| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file)?
+                .write_all(record.as_bytes())
+        });
+        if let Err(e) = written {
+            tracing::warn!(error = %e, file = %file.display(), "failed to record triggered skills");
+        }
+    }
+
+    Some(output)
+}
+
+/// File recording the triggered skills shown in the payload's session, if
+/// it has a usable `session_id`.
+fn shown_skills_file(cache_dir: &Path, payload: &HookPayload) -> Option<PathBuf> {
+    let session = payload.rest.get("session_id")?.as_str()?;
+    if session.is_empty()
+        || !session
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    Some(cache_dir.join("triggered-skills").join(session))
+}
+
+/// File holding the [`TriggerRecord`] of the workspace at `root`.
+fn trigger_record_file(cache_dir: &Path, root: &Path) -> PathBuf {
+    let hash = crate::server_cache::workspace_hash(root);
+    cache_dir
+        .join("trigger-workspaces")
+        .join(format!("{hash:016x}.json"))
+}
+
+/// What decides which skills with triggers apply in a workspace, and what
+/// was found from it.
+///
+/// Each input is kept apart, so that only what changed is recomputed: the
+/// dependencies when the workspace manifests change, and the skills when the
+/// config, the plugin sources, or the dependencies change. The plugin
+/// sources are only fingerprinted again once
+/// [`SOURCES_CHECK_INTERVAL`](crate::server_cache::SOURCES_CHECK_INTERVAL)
+/// has passed since the last time, since that walks every source directory.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TriggerRecord {
+    root: PathBuf,
+    config_mtime: Option<SystemTime>,
+    sources_fingerprint: u64,
+    sources_checked: SystemTime,
+    workspace_stamp: Vec<Option<SystemTime>>,
+    /// Name and version of each dependency, as of `workspace_stamp`.
+    dependencies: Vec<(String, String)>,
+    /// Whether any skill with triggers applies, as of the rest.
+    has_triggers: bool,
+}
+
+impl TriggerRecord {
+    /// The record in `file`, if it is one for `root`.
+    fn read(file: &Path, root: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(file).ok()?;
+        serde_json::from_str::<Self>(&text)
+            .ok()
+            .filter(|record| record.root == root)
+    }
+
+    /// The inputs for `root` as they are now, reusing what has not changed
+    /// since `previous`. `has_triggers` is copied from `previous`; it is up
+    /// to the caller to recompute it unless [`same_skills`](Self::same_skills).
+    fn current(root: &Path, previous: Option<&Self>) -> Self {
+        use crate::server_cache::{SOURCES_CHECK_INTERVAL, mtime, workspace_stamp};
+
+        let config_mtime = mtime(&crate::config::config_path());
+        let (sources_fingerprint, sources_checked) = match previous {
+            Some(previous)
+                if previous.config_mtime == config_mtime
+                    && previous
+                        .sources_checked
+                        .elapsed()
+                        .is_ok_and(|age| age < SOURCES_CHECK_INTERVAL) =>
+            {
+                (previous.sources_fingerprint, previous.sources_checked)
+            }
+            _ => (crate::plugins::source_fingerprint(), SystemTime::now()),
+        };
+
+        let stamp = workspace_stamp(root);
+        let (workspace_stamp, dependencies) = match previous {
+            Some(previous) if previous.workspace_stamp == stamp => {
+                (stamp, previous.dependencies.clone())
+            }
+            _ => {
+                let dependencies = crate::crate_sources::workspace_semver_pairs(root)
+                    .into_iter()
+                    .map(|(name, version)| (name, version.to_string()))
+                    .collect();
+                // `cargo metadata` may have written `Cargo.lock`; stamp
+                // afterwards so that write does not look like a change.
+                (workspace_stamp(root), dependencies)
+            }
+        };
+
+        TriggerRecord {
+            root: root.to_path_buf(),
+            config_mtime,
+            sources_fingerprint,
+            sources_checked,
+            workspace_stamp,
+            dependencies,
+            has_triggers: previous.is_some_and(|previous| previous.has_triggers),
+        }
+    }
+
+    /// Whether `other` has the same inputs for finding skills with triggers.
+    fn same_skills(&self, other: &Self) -> bool {
+        self.config_mtime == other.config_mtime
+            && self.sources_fingerprint == other.sources_fingerprint
+            && self.dependencies == other.dependencies
+    }
+
+    /// The dependencies, as used to match skills.
+    fn workspace(&self) -> Vec<(String, semver::Version)> {
+        self.dependencies
+            .iter()
+            .filter_map(|(name, version)| Some((name.clone(), version.parse().ok()?)))
+            .collect()
+    }
+
+    fn write(&self, file: &Path) {
+        let dir = file.parent().unwrap_or(file);
+        if !file.exists() {
+            remove_expired(dir);
+        }
+        let written = std::fs::create_dir_all(dir)
+            .and_then(|()| std::fs::write(file, serde_json::to_string(self)?));
+        if let Err(e) = written {
+            tracing::warn!(error = %e, file = %file.display(), "failed to record workspace triggers");
+        }
+    }
+}
+
+/// Remove the files in `dir` that were last modified more than
+/// [`RECORD_EXPIRY`] ago, such as the records of finished sessions.
+fn remove_expired(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let expired = entry.file_type().is_ok_and(|t| t.is_file())
+            && entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > RECORD_EXPIRY));
+        if expired && let Err(e) = std::fs::remove_file(entry.path()) {
+            tracing::warn!(error = %e, file = %entry.path().display(), "failed to remove expired record");
+        }
+    }
+}
+
+/// Describe a tool call with `input` for matching skill triggers.
+///
+/// The file is the input's `file_path` (or `notebook_path`), relative
+/// to `root` when it is inside it. The text is the file's current contents
+/// followed by any text the call writes (`content`, or the `new_string` of
+/// each edit).
+fn tool_use(payload: &PreToolUsePayload, input: &serde_json::Value, root: &Path) -> ToolUse {
+    let field = |key: &str| input.get(key).and_then(|value| value.as_str());
+    let path = field("file_path")
+        .or_else(|| field("notebook_path"))
+        .map(|path| root.join(path));
+
+    let mut text = String::new();
+    if let Some(path) = &path
+        && std::fs::metadata(path).is_ok_and(|m| m.len() <= MAX_TRIGGER_FILE_BYTES)
+        && let Ok(contents) = std::fs::read_to_string(path)
+    {
+        text.push_str(&contents);
+    }
+    let edits = input
+        .get("edits")
+        .and_then(|edits| edits.as_array())
+        .into_iter()
+        .flatten();
+    let written = [field("content"), field("new_string")]
+        .into_iter()
+        .chain(edits.map(|edit| edit.get("new_string").and_then(|s| s.as_str())))
+        .flatten();
+    for written in written {
+        text.push('\n');
+        text.push_str(written);
+    }
+
+    let file = path.map(|path| match path.strip_prefix(root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => path,
+    });
+    ToolUse {
+        tool: payload.tool_name.clone(),
+        file,
+        text,
+    }
 }
 
 /// Return all hooks (with their plugin name) that match the event in `payload`.
@@ -125,7 +558,7 @@ fn hooks_for_payload(
 
     let mut out = Vec::new();
 
-    for ParsedPlugin { path: _, plugin } in plugins {
+    for ParsedPlugin { plugin, .. } in plugins {
         let name = plugin.name.clone();
         for hook in &plugin.hooks {
             tracing::debug!(?hook);
@@ -168,6 +601,166 @@ mod tests {
             .try_init();
     }
 
+    #[test]
+    fn tool_use_from_claude_payload() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(
+            tmp.path().join("src/main.rs"),
+            "#[tokio::main]\nasync fn main() {}\n",
+        )
+        .unwrap();
+
+        let payload: HookPayload = serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "abc-123",
+            "cwd": tmp.path(),
+            "tool_name": "Edit",
+            "tool_input": {
+                "file_path": tmp.path().join("src/main.rs"),
+                "old_string": "async fn main() {}",
+                "new_string": "async fn main() { run().await }",
+            },
+        }))
+        .expect("parse payload");
+        let HookSubPayload::PreToolUse(pre_tool_use) = &payload.sub_payload;
+        let tool_use = tool_use(pre_tool_use, &payload.rest["tool_input"], tmp.path());
+
+        assert_eq!(tool_use.tool, "Edit");
+        assert_eq!(tool_use.file, Some(PathBuf::from("src/main.rs")));
+        assert!(tool_use.text.starts_with("#[tokio::main]\n"));
+        assert!(tool_use.text.ends_with("\nasync fn main() { run().await }"));
+        assert_eq!(
+            shown_skills_file(Path::new("/cache"), &payload),
+            Some(PathBuf::from("/cache/triggered-skills/abc-123"))
+        );
+    }
+
+    #[test]
+    fn trigger_record_reuses_unchanged_inputs() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let previous = TriggerRecord {
+            root: tmp.path().to_path_buf(),
+            config_mtime: crate::server_cache::mtime(&crate::config::config_path()),
+            sources_fingerprint: 42,
+            sources_checked: SystemTime::now(),
+            workspace_stamp: crate::server_cache::workspace_stamp(tmp.path()),
+            dependencies: vec![("serde".to_string(), "1.0.200".to_string())],
+            has_triggers: true,
+        };
+
+        // Nothing changed: no `cargo metadata`, and no walk of the sources.
+        let current = TriggerRecord::current(tmp.path(), Some(&previous));
+        assert_eq!(current, previous);
+        assert_eq!(
+            current.workspace(),
+            [("serde".to_string(), semver::Version::new(1, 0, 200))]
+        );
+
+        let file = tmp.path().join("records/record.json");
+        previous.write(&file);
+        assert_eq!(TriggerRecord::read(&file, tmp.path()), Some(previous));
+        assert_eq!(TriggerRecord::read(&file, Path::new("/elsewhere")), None);
+    }
+
+    #[test]
+    fn expired_records_are_removed() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let old = tmp.path().join("old-session");
+        let recent = tmp.path().join("recent-session");
+        fs::write(&old, "").unwrap();
+        fs::write(&recent, "").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - RECORD_EXPIRY - Duration::from_secs(60))
+            .unwrap();
+
+        remove_expired(tmp.path());
+        assert!(!old.exists());
+        assert!(recent.exists());
+    }
+
+    #[test]
+    fn hook_outputs_merge_into_one_document() {
+        let output = |stdout: serde_json::Value| HookOutput {
+            stdout: stdout.to_string(),
+            blocked: false,
+        };
+        let outputs = [
+            output(serde_json::json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "allow",
+                    "additionalContext": "from one",
+                },
+                "systemMessage": "first",
+            })),
+            output(serde_json::json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "deny",
+                    "permissionDecisionReason": "not allowed",
+                },
+                "systemMessage": "second",
+            })),
+            HookOutput {
+                stdout: "plain text\n".to_string(),
+                blocked: false,
+            },
+        ];
+
+        let merged = merge_outputs(&outputs, Some("triggered".to_string())).unwrap();
+        assert_eq!(
+            merged,
+            serde_json::json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "deny",
+                    "permissionDecisionReason": "not allowed",
+                    "additionalContext": "from one\n\nplain text\n\ntriggered",
+                },
+                "systemMessage": "first",
+            })
+        );
+        assert_eq!(
+            merge_outputs(&outputs[2..], None),
+            Some(serde_json::json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "additionalContext": "plain text",
+                },
+            }))
+        );
+        assert_eq!(merge_outputs(&[], None), None);
+    }
+
+    #[test]
+    fn strictest_permission_decision_wins() {
+        let output = |decision: &str| HookOutput {
+            stdout: serde_json::json!({
+                "hookSpecificOutput": { "permissionDecision": decision }
+            })
+            .to_string(),
+            blocked: false,
+        };
+        assert_eq!(permission_decision(&[]), None);
+        assert_eq!(
+            permission_decision(&[output("allow"), output("ask")]),
+            Some(PermissionDecision::Ask)
+        );
+        let blocked = HookOutput {
+            stdout: "not json".to_string(),
+            blocked: true,
+        };
+        assert_eq!(
+            permission_decision(&[output("allow"), blocked]),
+            Some(PermissionDecision::Deny)
+        );
+    }
+
     #[tokio::test]
     async fn plugin_hooks_run_and_create_files() {
         setup_tracing();
@@ -3,13 +3,20 @@ use std::process::ExitCode;
 
 use crate::plugins::ParsedPlugin;
 
-mod predicate;
+mod acp;
+mod cargo_wrapper;
 mod config;
 mod crate_sources;
+mod diagnostics;
 mod git_source;
 mod hook;
 mod mcp;
 mod plugins;
+mod predicate;
+mod progress;
+mod rust_command;
+mod server_cache;
+mod skill_export;
 mod skills;
 pub mod tutorial;
 
@@ -29,8 +36,23 @@ enum Commands {
     /// Show the Symposium tutorial for agents and humans
     Tutorial,
 
-    /// Run as an MCP server (stdio transport)
-    Mcp,
+    /// Run as an MCP server (stdio transport by default)
+    Mcp {
+        /// Serve over streamable HTTP on this address (e.g. 127.0.0.1:8080) instead of stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<std::net::SocketAddr>,
+
+        /// Require this bearer token on HTTP requests (ignored without --http)
+        #[arg(long, env = "SYMPOSIUM_MCP_TOKEN")]
+        token: Option<String>,
+    },
+
+    /// Run as an ACP proxy between an editor and an agent
+    Acp {
+        /// The agent command and its arguments (e.g., "-- claude-code-acp")
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        agent: Vec<String>,
+    },
 
     /// Handle a hook event (invoked by editor plugins)
     Hook {
@@ -40,14 +62,29 @@ enum Commands {
 
     /// Get Rust development guidance
     Rust {
-        /// The command to run (e.g., "help")
-        #[arg(default_value = "help")]
-        command: String,
+        /// The command to run and its arguments (e.g., "help", "crate serde"); defaults to help
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Run cargo with compact, agent-friendly output
+    Cargo {
+        /// Show the full text of diagnostic <N> from the last run instead of running cargo
+        #[arg(long, value_name = "N")]
+        detail: Option<usize>,
+
+        /// The cargo subcommand and its arguments (e.g., "check", "test --lib")
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
     },
 
     /// Find crate sources and guidance
+    #[command(args_conflicts_with_subcommands = true, disable_help_subcommand = true)]
     Crate {
-        /// Crate name (omit to use --list)
+        #[command(subcommand)]
+        command: Option<CrateCommand>,
+
+        /// Crate name (omit to use --list); write `-- api` for a crate named `api`
         name: Option<String>,
 
         /// Version constraint (e.g., "1.0.3", "^1.0"); defaults to workspace version or latest
@@ -57,6 +94,14 @@ enum Commands {
         /// List all workspace dependency crates
         #[arg(long)]
         list: bool,
+
+        /// Limit guidance to about this many tokens (default: from config, else unlimited)
+        #[arg(long)]
+        budget: Option<usize>,
+
+        /// Include the text of skill resource files (examples, reference docs) in the guidance
+        #[arg(long)]
+        with_resources: bool,
     },
 
     /// Manage plugins
@@ -64,6 +109,43 @@ enum Commands {
         #[command(subcommand)]
         command: PluginCommand,
     },
+
+    /// Manage skills
+    Skills {
+        #[command(subcommand)]
+        command: SkillsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum SkillsCommand {
+    /// Write the skills for the current workspace to an agent's native skills directory
+    Export {
+        /// Agent whose skills directory to write
+        #[arg(long, value_enum)]
+        target: skill_export::ExportTarget,
+
+        /// Write to the workspace's skills directory (the default)
+        #[arg(long, conflicts_with = "user")]
+        project: bool,
+
+        /// Write to the user's skills directory
+        #[arg(long)]
+        user: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CrateCommand {
+    /// Outline a crate's public API (modules, types, traits, function signatures)
+    Api {
+        /// Crate name
+        name: String,
+
+        /// Version constraint (e.g., "1.0.3", "^1.0"); defaults to workspace version or latest
+        #[arg(long)]
+        version: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -83,9 +165,9 @@ enum PluginCommand {
         plugin: String,
     },
 
-    /// Validate a plugin source directory or a single TOML manifest
+    /// Validate a plugin source directory, a single TOML manifest, or a single SKILL.md
     Validate {
-        /// Path to a directory (scanned for .toml plugins and SKILL.md files) or a single .toml file
+        /// Path to a directory (scanned for .toml plugins and SKILL.md files), a single .toml file, or a SKILL.md
         path: std::path::PathBuf,
 
         /// Skip checking that crate names in predicates exist on crates.io
@@ -108,26 +190,87 @@ async fn main() -> ExitCode {
             print!("{}", tutorial::render_cli());
             ExitCode::SUCCESS
         }
-        Some(Commands::Mcp) => match mcp::serve().await {
+        Some(Commands::Mcp { http, token }) => {
+            let result = match http {
+                Some(addr) => mcp::serve_http(addr, token).await,
+                None => {
+                    if token.is_some() {
+                        tracing::warn!("ignoring MCP token: it only applies with --http");
+                    }
+                    mcp::serve().await
+                }
+            };
+            match result {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("MCP server error: {e}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Some(Commands::Acp { agent }) => match acp::run(&agent).await {
             Ok(()) => ExitCode::SUCCESS,
             Err(e) => {
-                eprintln!("MCP server error: {e}");
+                eprintln!("ACP proxy error: {e}");
                 ExitCode::FAILURE
             }
         },
         Some(Commands::Hook { event }) => hook::run(event).await,
         Some(Commands::Rust { command }) => {
-            print!("{}", mcp::execute_rust_command(&command));
-            ExitCode::SUCCESS
+            let cwd = std::env::current_dir().expect("failed to get current directory");
+            let workspace = crate_sources::workspace_semver_pairs(&cwd);
+            let registry = plugins::load_registry();
+            match rust_command::execute(&command, &registry, &cwd, &workspace).await {
+                Ok(output) => {
+                    print!("{output}");
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Some(Commands::Cargo { detail, args }) => {
+            let cwd = std::env::current_dir().expect("failed to get current directory");
+            let result = match detail {
+                Some(index) => cargo_wrapper::detail_output(&cwd, index),
+                None => cargo_wrapper::run_output(&cwd, &args).await,
+            };
+            match result {
+                Ok(output) => {
+                    print!("{output}");
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    ExitCode::FAILURE
+                }
+            }
         }
         Some(Commands::Crate {
+            command,
             name,
             version,
             list,
+            budget,
+            with_resources,
         }) => {
             let cwd = std::env::current_dir().expect("failed to get current directory");
 
-            if list {
+            if let Some(CrateCommand::Api { name, version }) = command {
+                let workspace = crate_sources::workspace_semver_pairs(&cwd);
+                match crate_sources::api_output(&name, version.as_deref(), &workspace).await {
+                    Ok(output) => {
+                        print!("{output}");
+                        ExitCode::SUCCESS
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        ExitCode::FAILURE
+                    }
+                }
+            } else if list {
                 let workspace = crate_sources::workspace_semver_pairs(&cwd);
                 let registry = plugins::load_registry();
                 print!("{}", skills::list_output(&registry, &workspace).await);
@@ -135,7 +278,19 @@ async fn main() -> ExitCode {
             } else if let Some(name) = name {
                 let workspace = crate_sources::workspace_semver_pairs(&cwd);
                 let registry = plugins::load_registry();
-                match skills::info_output(&name, version.as_deref(), &registry, &workspace).await {
+                match skills::info_output(
+                    &name,
+                    version.as_deref(),
+                    skills::GuidanceOptions {
+                        budget,
+                        with_resources,
+                    },
+                    &registry,
+                    &cwd,
+                    &workspace,
+                )
+                .await
+                {
                     Ok(output) => {
                         print!("{output}");
                         ExitCode::SUCCESS
@@ -264,9 +419,20 @@ async fn main() -> ExitCode {
                     } else {
                         ExitCode::SUCCESS
                     }
+                } else if path.file_name().is_some_and(|name| name == "SKILL.md") {
+                    match plugins::validate_skill_file(&path) {
+                        Ok(()) => {
+                            println!("ok: {} (skill)", path.display());
+                            ExitCode::SUCCESS
+                        }
+                        Err(e) => {
+                            eprintln!("FAIL: {} (skill): {e:#}", path.display());
+                            ExitCode::FAILURE
+                        }
+                    }
                 } else {
                     match plugins::load_plugin(&path) {
-                        Ok(ParsedPlugin { path: _, plugin }) => {
+                        Ok(ParsedPlugin { plugin, .. }) => {
                             println!("{}", toml::to_string_pretty(&plugin).unwrap());
                             ExitCode::SUCCESS
                         }
@@ -278,7 +444,7 @@ async fn main() -> ExitCode {
                 }
             }
             PluginCommand::Show { plugin } => match plugins::find_plugin(&plugin) {
-                Some(ParsedPlugin { path, plugin }) => {
+                Some(ParsedPlugin { path, plugin, .. }) => {
                     println!("# Source: {}", path.display());
                     println!();
                     print!("{}", toml::to_string_pretty(&plugin).unwrap());
@@ -290,6 +456,33 @@ async fn main() -> ExitCode {
                 }
             },
         },
+        Some(Commands::Skills { command }) => match command {
+            SkillsCommand::Export {
+                target,
+                project: _,
+                user,
+            } => {
+                let cwd = std::env::current_dir().expect("failed to get current directory");
+                let workspace = crate_sources::workspace_semver_pairs(&cwd);
+                let registry = plugins::load_registry();
+                let scope = if user {
+                    skill_export::ExportScope::User
+                } else {
+                    skill_export::ExportScope::Project
+                };
+                match skill_export::export_output(target, scope, &registry, &cwd, &workspace).await
+                {
+                    Ok(output) => {
+                        print!("{output}");
+                        ExitCode::SUCCESS
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {e:#}");
+                        ExitCode::FAILURE
+                    }
+                }
+            }
+        },
         None => {
             println!("symposium — AI the Rust Way");
             println!();
@@ -299,8 +492,11 @@ async fn main() -> ExitCode {
             println!("  tutorial   Show the Symposium tutorial for agents and humans");
             println!("  rust       Get Rust development guidance");
             println!("  crate      Find crate sources and guidance");
+            println!("  cargo      Run cargo with compact, agent-friendly output");
             println!("  plugin     Manage plugins");
+            println!("  skills     Manage skills");
             println!("  mcp        Run as an MCP server (stdio transport)");
+            println!("  acp        Run as an ACP proxy between an editor and an agent");
             println!("  hook       Handle a hook event (invoked by editor plugins)");
             println!("  help       Show this message");
             println!();
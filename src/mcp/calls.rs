@@ -0,0 +1,250 @@
+//! Progress and cancellation for in-flight tool calls
+//!
+//! The MCP server library hands tools only their arguments: the request id,
+//! `_meta.progressToken`, and `notifications/cancelled` never reach them. To
+//! support progress notifications and cancellation, the transport passes each
+//! incoming message through [`ToolCalls::intercept`], which records every
+//! `tools/call` and tags its arguments with a hidden [`CALL_ARG`] key. The
+//! tool then claims its entry with [`ToolCalls::begin`]. The same pass keeps
+//! the connection's [`ClientRoots`] up to date.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use sacp::UntypedMessage;
+use sacp::mcp_server::McpConnectionTo;
+use sacp::role;
+use tokio_util::sync::CancellationToken;
+
+use super::roots::ClientRoots;
+use crate::progress;
+
+/// Argument key the transport adds to `tools/call` arguments.
+pub const CALL_ARG: &str = "_symposium_call";
+
+/// Tool calls currently in flight on one connection.
+#[derive(Default)]
+pub struct ToolCalls {
+    calls: Mutex<HashMap<String, CallState>>,
+    /// The client's roots, which tools use to find their workspace.
+    pub roots: ClientRoots,
+}
+
+struct CallState {
+    progress_token: Option<serde_json::Value>,
+    cancel: CancellationToken,
+}
+
+impl ToolCalls {
+    /// Inspect a message from the client before it reaches the server.
+    ///
+    /// Returns the line to forward, which differs from the input only for
+    /// `tools/call` requests.
+    pub fn intercept(&self, line: &str) -> String {
+        let Ok(mut message) = serde_json::from_str::<serde_json::Value>(line) else {
+            return line.to_string();
+        };
+
+        match message.get("method").and_then(|m| m.as_str()) {
+            Some("tools/call") => {
+                let Some(key) = message.get("id").map(|id| id.to_string()) else {
+                    return line.to_string();
+                };
+                let Some(params) = message.get_mut("params").and_then(|p| p.as_object_mut()) else {
+                    return line.to_string();
+                };
+                let progress_token = params
+                    .get("_meta")
+                    .and_then(|meta| meta.get("progressToken"))
+                    .cloned();
+
+                let arguments = params
+                    .entry("arguments")
+                    .or_insert_with(|| serde_json::json!({}));
+                let Some(arguments) = arguments.as_object_mut() else {
+                    return line.to_string();
+                };
+                arguments.insert(CALL_ARG.to_string(), key.clone().into());
+
+                self.calls.lock().unwrap().insert(
+                    key,
+                    CallState {
+                        progress_token,
+                        cancel: CancellationToken::new(),
+                    },
+                );
+                message.to_string()
+            }
+            Some("notifications/cancelled") => {
+                let request_id = message
+                    .get("params")
+                    .and_then(|p| p.get("requestId"))
+                    .map(|id| id.to_string());
+                if let Some(state) = request_id
+                    .as_ref()
+                    .and_then(|key| self.calls.lock().unwrap().remove(key))
+                {
+                    tracing::debug!(request_id = ?request_id, "tool call cancelled");
+                    state.cancel.cancel();
+                }
+                line.to_string()
+            }
+            Some(method) => {
+                self.roots.observe(method, &message);
+                line.to_string()
+            }
+            None => line.to_string(),
+        }
+    }
+
+    /// Claim the state recorded for a call.
+    ///
+    /// Calls that were not intercepted (e.g. `key` is `None`) get a call that
+    /// reports nothing and is never cancelled.
+    pub fn begin(
+        self: &Arc<Self>,
+        key: Option<&str>,
+        cx: &McpConnectionTo<role::mcp::Client>,
+    ) -> ToolCall {
+        let state = key.and_then(|k| {
+            let calls = self.calls.lock().unwrap();
+            calls
+                .get(k)
+                .map(|s| (s.progress_token.clone(), s.cancel.clone()))
+        });
+        let (progress_token, cancel) = state.unwrap_or_default();
+
+        ToolCall {
+            key: key.map(str::to_string),
+            calls: self.clone(),
+            reporter: progress_token.map(|token| progress_reporter(token, cx)),
+            cancel,
+        }
+    }
+}
+
+/// A tool call in progress. Dropping it forgets the call.
+pub struct ToolCall {
+    key: Option<String>,
+    calls: Arc<ToolCalls>,
+    reporter: Option<progress::Reporter>,
+    cancel: CancellationToken,
+}
+
+impl ToolCall {
+    /// Run the tool's work, reporting its progress to the client and
+    /// abandoning it (dropping the future) if the client cancels the call.
+    pub async fn run<T>(
+        &self,
+        work: impl Future<Output = Result<T, sacp::Error>>,
+    ) -> Result<T, sacp::Error> {
+        let work = async {
+            match &self.reporter {
+                Some(reporter) => progress::with_reporter(reporter.clone(), work).await,
+                None => work.await,
+            }
+        };
+        tokio::select! {
+            result = work => result,
+            () = self.cancel.cancelled() => Err(sacp::util::internal_error("request cancelled")),
+        }
+    }
+}
+
+impl Drop for ToolCall {
+    fn drop(&mut self) {
+        if let Some(key) = &self.key {
+            self.calls.calls.lock().unwrap().remove(key);
+        }
+    }
+}
+
+/// Build a reporter that sends `notifications/progress` for `token`.
+fn progress_reporter(
+    token: serde_json::Value,
+    cx: &McpConnectionTo<role::mcp::Client>,
+) -> progress::Reporter {
+    let connection = cx.connection_to();
+    let count = Mutex::new(0u64);
+    Arc::new(move |message: &str| {
+        // Progress values must increase with every notification.
+        let progress = {
+            let mut count = count.lock().unwrap();
+            *count += 1;
+            *count
+        };
+        let result = UntypedMessage::new(
+            "notifications/progress",
+            serde_json::json!({
+                "progressToken": token,
+                "progress": progress,
+                "message": message,
+            }),
+        )
+        .and_then(|notification| connection.send_notification(notification));
+        if let Err(e) = result {
+            tracing::debug!(error = %e, "failed to send progress notification");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intercept_tags_tool_calls() {
+        let calls = ToolCalls::default();
+        let line = calls.intercept(
+            r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"crate","arguments":{"command":"List"},"_meta":{"progressToken":"tok"}}}"#,
+        );
+        let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(message["params"]["arguments"][CALL_ARG], "7");
+        assert_eq!(message["params"]["arguments"]["command"], "List");
+
+        let state = &calls.calls.lock().unwrap()["7"];
+        assert_eq!(state.progress_token, Some("tok".into()));
+        assert!(!state.cancel.is_cancelled());
+    }
+
+    #[test]
+    fn intercept_cancels_matching_call() {
+        let calls = ToolCalls::default();
+        calls.intercept(
+            r#"{"jsonrpc":"2.0","id":"a","method":"tools/call","params":{"name":"crate"}}"#,
+        );
+        let cancel = calls.calls.lock().unwrap()[r#""a""#].cancel.clone();
+
+        calls.intercept(
+            r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":"a"}}"#,
+        );
+        assert!(cancel.is_cancelled());
+        assert!(calls.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_call_drops_its_work() {
+        let calls = Arc::new(ToolCalls::default());
+        let cancel = CancellationToken::new();
+        let call = ToolCall {
+            key: None,
+            calls,
+            reporter: None,
+            cancel: cancel.clone(),
+        };
+
+        cancel.cancel();
+        let result = call
+            .run(std::future::pending::<Result<(), sacp::Error>>())
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn intercept_passes_other_messages_through() {
+        let calls = ToolCalls::default();
+        let line = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#;
+        assert_eq!(calls.intercept(line), line);
+        assert_eq!(calls.intercept("not json"), "not json");
+    }
+}
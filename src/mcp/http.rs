@@ -0,0 +1,631 @@
+//! Streamable HTTP transport for the MCP server
+//!
+//! Implements the parts of the MCP streamable HTTP transport that Symposium
+//! needs, so one server can be shared by several clients (e.g. a devcontainer
+//! or a remote agent):
+//!
+//! * `POST /mcp` carries one JSON-RPC message. An `initialize` request starts
+//!   a session, identified by the `Mcp-Session-Id` response header; later
+//!   messages must send that header back. Requests are answered with an SSE
+//!   stream that carries any server-to-client messages sent while the request
+//!   is in flight (such as `roots/list`) and ends with the response.
+//!   Notifications and responses are acknowledged with `202 Accepted`.
+//! * `DELETE /mcp` ends a session. Sessions idle for longer than
+//!   [`SESSION_IDLE_TIMEOUT`] are ended as well.
+//!
+//! To guard against DNS rebinding, requests with an `Origin` header are only
+//! accepted from loopback origins (see [`allowed_origin`]).
+//!
+//! Each session runs its own [`build_server`](super::build_server) instance
+//! over an in-memory pipe, with client messages passed through
+//! [`ToolCalls::intercept`], and its own [`PluginServers`] started from the
+//! session's roots; all sessions share one [`ServerCache`].
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use bytes::Bytes;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use sacp::{ByteStreams, ConnectTo};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use super::{PluginServers, ToolCalls};
+use crate::server_cache::ServerCache;
+
+/// Path the MCP endpoint is served on.
+const MCP_PATH: &str = "/mcp";
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// How long a session may go without requests before it is ended.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often to look for idle sessions.
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+type Body = UnsyncBoxBody<Bytes, Infallible>;
+
+/// Open SSE streams, keyed by the id of the request each one waits on, and
+/// server messages waiting for a stream to open.
+#[derive(Default)]
+struct Streams {
+    open: Vec<(serde_json::Value, mpsc::UnboundedSender<String>)>,
+    queued: Vec<String>,
+}
+
+type PendingStreams = Mutex<Streams>;
+
+/// Serve the MCP tools over streamable HTTP on `addr`.
+///
+/// If `token` is set, every request must carry `Authorization: Bearer <token>`.
+pub async fn serve_http(addr: SocketAddr, token: Option<String>) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    let local = listener.local_addr()?;
+    tracing::info!(addr = %local, "serving MCP over HTTP");
+    eprintln!("Symposium MCP server listening on http://{local}{MCP_PATH}");
+    serve_listener(listener, token).await
+}
+
+async fn serve_listener(listener: TcpListener, token: Option<String>) -> Result<()> {
+    let state = Arc::new(HttpState {
+        token,
+        cache: ServerCache::shared(),
+        sessions: Mutex::new(HashMap::new()),
+    });
+
+    tokio::spawn({
+        let state = state.clone();
+        async move {
+            let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                state.end_idle_sessions(SESSION_IDLE_TIMEOUT);
+            }
+        }
+    });
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle(state.clone(), req));
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(%peer, error = %e, "HTTP connection error");
+            }
+        });
+    }
+}
+
+struct HttpState {
+    token: Option<String>,
+    cache: Arc<ServerCache>,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+}
+
+impl HttpState {
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+    }
+
+    /// End sessions that have had no requests for `timeout` and have no
+    /// request in flight.
+    fn end_idle_sessions(&self, timeout: Duration) {
+        self.sessions.lock().unwrap().retain(|id, session| {
+            let keep = session.last_used.lock().unwrap().elapsed() < timeout
+                || !session.streams.lock().unwrap().open.is_empty();
+            if !keep {
+                tracing::debug!(session = %id, "ending idle MCP session");
+            }
+            keep
+        });
+    }
+}
+
+/// Compare two byte strings in time that depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Whether a request's `Origin` header (if any) is a loopback origin.
+///
+/// Browsers send `Origin` on cross-origin requests, so a page whose host name
+/// was rebound to this machine shows up with its own, non-loopback origin.
+/// Clients outside a browser usually send no `Origin` at all.
+fn allowed_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Some(url) = origin.to_str().ok().and_then(|o| url::Url::parse(o).ok()) else {
+        return false;
+    };
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// One client session: an MCP server instance plus routing for its output.
+struct Session {
+    /// JSON-RPC messages from the client, one per line.
+    input: mpsc::UnboundedSender<String>,
+    streams: Arc<PendingStreams>,
+    tasks: Vec<JoinHandle<()>>,
+    /// When the client last sent a message, for the idle timeout.
+    last_used: Mutex<Instant>,
+}
+
+impl Session {
+    fn start(cache: Arc<ServerCache>) -> Self {
+        let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_end);
+        let (client_read, mut client_write) = tokio::io::split(client_end);
+
+        let calls = Arc::new(ToolCalls::default());
+        let server = tokio::spawn({
+            let cache = cache.clone();
+            let calls = calls.clone();
+            async move {
+                let transport = ByteStreams::new(server_write.compat_write(), server_read.compat());
+                if let Err(e) = super::build_server(cache, calls)
+                    .connect_to(transport)
+                    .await
+                {
+                    tracing::warn!(error = %e, "MCP session ended with error");
+                }
+            }
+        });
+
+        let streams = Arc::new(Mutex::new(Streams::default()));
+        let (client, mut client_rx) = mpsc::unbounded_channel();
+        let notify = tokio::spawn(super::notify_skill_changes(cache.clone(), client.clone()));
+        let plugin_servers = PluginServers::new(client);
+        let plugin_output = tokio::spawn({
+            let streams = streams.clone();
+            async move {
+                while let Some(line) = client_rx.recv().await {
+                    route_server_message(&streams, line);
+                }
+            }
+        });
+
+        let (input, mut input_rx) = mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn({
+            let plugin_servers = plugin_servers.clone();
+            let streams = streams.clone();
+            async move {
+                while let Some(line) = input_rx.recv().await {
+                    if let Some(reply) = plugin_servers.route(&line) {
+                        let streams = streams.clone();
+                        tokio::spawn(async move {
+                            if let Some(reply) = reply.await {
+                                route_server_message(&streams, reply);
+                            }
+                        });
+                        continue;
+                    }
+                    let line = calls.intercept(&line);
+                    if super::is_initialized(&line) {
+                        tokio::spawn(super::start_plugin_servers(
+                            cache.clone(),
+                            plugin_servers.clone(),
+                            calls.clone(),
+                        ));
+                    }
+                    if client_write.write_all(line.as_bytes()).await.is_err()
+                        || client_write.write_all(b"\n").await.is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let reader = tokio::spawn({
+            let streams = streams.clone();
+            async move {
+                let mut lines = BufReader::new(client_read).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    route_server_message(&streams, plugin_servers.extend_response(line));
+                }
+            }
+        });
+
+        Session {
+            input,
+            streams,
+            tasks: vec![server, writer, reader, plugin_output, notify],
+            last_used: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Send a request and return a receiver for the messages of its SSE stream.
+    fn request(&self, id: serde_json::Value, line: String) -> mpsc::UnboundedReceiver<String> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        // Register before sending so the response cannot arrive first.
+        let mut streams = self.streams.lock().unwrap();
+        for line in streams.queued.drain(..) {
+            let _ = tx.send(line);
+        }
+        streams.open.push((id, tx));
+        drop(streams);
+        let _ = self.input.send(line);
+        rx
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Deliver a message from the server to the right SSE stream.
+///
+/// Responses go to the stream of the request they answer, which then closes.
+/// Server-initiated requests and notifications go to the oldest open stream,
+/// or, if none is open, to the next one that opens; the client answers them
+/// with a separate POST.
+fn route_server_message(streams: &PendingStreams, line: String) {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(&line) else {
+        tracing::warn!(%line, "server sent invalid JSON");
+        return;
+    };
+
+    let mut streams = streams.lock().unwrap();
+    if message.get("method").is_none() {
+        let id = message.get("id");
+        if let Some(pos) = streams
+            .open
+            .iter()
+            .position(|(pending, _)| Some(pending) == id)
+        {
+            let (_, tx) = streams.open.remove(pos);
+            let _ = tx.send(line);
+            return;
+        }
+        tracing::debug!(%line, "no open stream for server message; dropping it");
+    } else if let Some((_, tx)) = streams.open.first() {
+        let _ = tx.send(line);
+    } else {
+        streams.queued.push(line);
+    }
+}
+
+async fn handle(
+    state: Arc<HttpState>,
+    req: Request<Incoming>,
+) -> Result<Response<Body>, Infallible> {
+    if req.uri().path() != MCP_PATH {
+        return Ok(status(StatusCode::NOT_FOUND, "not found"));
+    }
+    if !allowed_origin(req.headers()) {
+        return Ok(status(StatusCode::FORBIDDEN, "origin not allowed"));
+    }
+    if !state.authorized(req.headers()) {
+        let mut response = status(StatusCode::UNAUTHORIZED, "missing or invalid bearer token");
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        return Ok(response);
+    }
+
+    let session_id = req
+        .headers()
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    match *req.method() {
+        Method::POST => Ok(handle_post(&state, session_id, req).await),
+        Method::DELETE => {
+            let removed = session_id.and_then(|id| state.sessions.lock().unwrap().remove(&id));
+            Ok(match removed {
+                Some(_) => status(StatusCode::NO_CONTENT, ""),
+                None => status(StatusCode::NOT_FOUND, "unknown session"),
+            })
+        }
+        _ => {
+            let mut response = status(StatusCode::METHOD_NOT_ALLOWED, "use POST or DELETE");
+            response
+                .headers_mut()
+                .insert(header::ALLOW, HeaderValue::from_static("POST, DELETE"));
+            Ok(response)
+        }
+    }
+}
+
+async fn handle_post(
+    state: &HttpState,
+    session_id: Option<String>,
+    req: Request<Incoming>,
+) -> Response<Body> {
+    let body = match req.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => {
+            return status(
+                StatusCode::BAD_REQUEST,
+                &format!("failed to read body: {e}"),
+            );
+        }
+    };
+    let message: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => return status(StatusCode::BAD_REQUEST, &format!("invalid JSON: {e}")),
+    };
+    if !message.is_object() {
+        return status(
+            StatusCode::BAD_REQUEST,
+            "expected a single JSON-RPC message; batches are not supported",
+        );
+    }
+    // Re-serialize so the message is guaranteed to fit on one line.
+    let line = message.to_string();
+
+    let method = message.get("method").and_then(|m| m.as_str());
+    let (session_id, session, created) = if method == Some("initialize") {
+        let id = uuid::Uuid::new_v4().to_string();
+        let session = Arc::new(Session::start(state.cache.clone()));
+        state
+            .sessions
+            .lock()
+            .unwrap()
+            .insert(id.clone(), session.clone());
+        (id, session, true)
+    } else {
+        let Some(id) = session_id else {
+            return status(StatusCode::BAD_REQUEST, "missing Mcp-Session-Id header");
+        };
+        let Some(session) = state.sessions.lock().unwrap().get(&id).cloned() else {
+            return status(StatusCode::NOT_FOUND, "unknown session");
+        };
+        *session.last_used.lock().unwrap() = Instant::now();
+        (id, session, false)
+    };
+
+    let request_id = message.get("id").filter(|_| method.is_some()).cloned();
+    let mut response = match request_id {
+        Some(id) => sse_response(session.request(id, line)),
+        None => {
+            let _ = session.input.send(line);
+            status(StatusCode::ACCEPTED, "")
+        }
+    };
+    if created && let Ok(value) = HeaderValue::from_str(&session_id) {
+        response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    response
+}
+
+/// Stream messages as server-sent events until the sender side closes.
+fn sse_response(rx: mpsc::UnboundedReceiver<String>) -> Response<Body> {
+    let events = futures::stream::unfold(rx, |mut rx| async move {
+        let line = rx.recv().await?;
+        let event = format!("event: message\ndata: {line}\n\n");
+        Some((Ok(Frame::data(Bytes::from(event))), rx))
+    });
+
+    let mut response = Response::new(StreamBody::new(events).boxed_unsync());
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/event-stream"),
+    );
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+fn status(code: StatusCode, message: &str) -> Response<Body> {
+    let body = if message.is_empty() {
+        Empty::new().boxed_unsync()
+    } else {
+        Full::new(Bytes::from(format!("{message}\n"))).boxed_unsync()
+    };
+    let mut response = Response::new(body);
+    *response.status_mut() = code;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn start_server(token: Option<&str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{MCP_PATH}", listener.local_addr().unwrap());
+        tokio::spawn(serve_listener(listener, token.map(str::to_string)));
+        url
+    }
+
+    fn post(url: &str, body: serde_json::Value) -> reqwest::RequestBuilder {
+        reqwest::Client::new()
+            .post(url)
+            .header(header::ACCEPT, "application/json, text/event-stream")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+    }
+
+    /// Parse the `data:` payloads of an SSE body.
+    fn sse_messages(body: &str) -> Vec<serde_json::Value> {
+        body.lines()
+            .filter_map(|l| l.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_token() {
+        let url = start_server(Some("secret")).await;
+        let response = post(&url, serde_json::json!({})).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_foreign_origin() {
+        let url = start_server(None).await;
+        let response = post(&url, serde_json::json!({}))
+            .header(header::ORIGIN, "http://attacker.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn allows_loopback_origins() {
+        let mut headers = HeaderMap::new();
+        assert!(allowed_origin(&headers));
+        for origin in [
+            "http://localhost:3000",
+            "http://127.0.0.1:8080",
+            "http://[::1]",
+        ] {
+            headers.insert(header::ORIGIN, HeaderValue::from_static(origin));
+            assert!(allowed_origin(&headers), "{origin}");
+        }
+        headers.insert(header::ORIGIN, HeaderValue::from_static("null"));
+        assert!(!allowed_origin(&headers));
+    }
+
+    #[test]
+    fn token_comparison() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+
+    #[tokio::test]
+    async fn idle_sessions_end() {
+        let cache = Arc::new(ServerCache::default());
+        let state = HttpState {
+            token: None,
+            cache: cache.clone(),
+            sessions: Mutex::new(HashMap::new()),
+        };
+        let session = Arc::new(Session::start(cache));
+        state
+            .sessions
+            .lock()
+            .unwrap()
+            .insert("s".to_string(), session);
+
+        state.end_idle_sessions(SESSION_IDLE_TIMEOUT);
+        assert_eq!(state.sessions.lock().unwrap().len(), 1);
+        state.end_idle_sessions(Duration::ZERO);
+        assert!(state.sessions.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_session() {
+        let url = start_server(None).await;
+        let response = post(
+            &url,
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        )
+        .header(SESSION_HEADER, "nope")
+        .send()
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn session_round_trip() {
+        let url = start_server(Some("secret")).await;
+
+        let response = post(
+            &url,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "0" }
+                }
+            }),
+        )
+        .bearer_auth("secret")
+        .send()
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let session = response.headers()[SESSION_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let messages = sse_messages(&response.text().await.unwrap());
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["id"], 1);
+        assert!(messages[0]["result"]["capabilities"]["tools"].is_object());
+
+        let response = post(
+            &url,
+            serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        )
+        .bearer_auth("secret")
+        .header(SESSION_HEADER, &session)
+        .send()
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let response = post(
+            &url,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "rust", "arguments": { "command": "help" } }
+            }),
+        )
+        .bearer_auth("secret")
+        .header(SESSION_HEADER, &session)
+        .send()
+        .await
+        .unwrap();
+        let messages = sse_messages(&response.text().await.unwrap());
+        let last = messages.last().unwrap();
+        assert_eq!(last["id"], 2);
+        assert_eq!(last["result"]["isError"], false);
+
+        let response = reqwest::Client::new()
+            .delete(&url)
+            .bearer_auth("secret")
+            .header(SESSION_HEADER, &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+}
@@ -0,0 +1,551 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use sacp::mcp_server::{McpConnectionTo, McpServer};
+use sacp::role;
+use sacp::{ByteStreams, ConnectTo, RunWithConnectionTo};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use crate::cargo_wrapper;
+use crate::crate_sources;
+use crate::plugins::PluginRegistry;
+use crate::rust_command;
+use crate::server_cache::ServerCache;
+use crate::skills;
+
+mod calls;
+mod http;
+mod plugin_servers;
+mod roots;
+
+use calls::ToolCalls;
+pub use http::serve_http;
+use plugin_servers::PluginServers;
+use roots::ClientRoots;
+
+pub async fn serve() -> Result<()> {
+    let cache = ServerCache::shared();
+    let calls = Arc::new(ToolCalls::default());
+    let server = build_server(cache.clone(), calls.clone());
+    let stdout = Arc::new(tokio::sync::Mutex::new(tokio::io::stdout()));
+
+    // Messages from the plugin servers to the client.
+    let (client, mut client_rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(notify_skill_changes(cache.clone(), client.clone()));
+    let plugin_servers = PluginServers::new(client);
+    tokio::spawn({
+        let stdout = stdout.clone();
+        async move {
+            while let Some(line) = client_rx.recv().await {
+                if !write_line(&stdout, &line).await {
+                    break;
+                }
+            }
+        }
+    });
+
+    // Feed stdin to the server through `ToolCalls::intercept`, answering
+    // calls to plugin tools directly.
+    let (mut input, server_input) = tokio::io::duplex(64 * 1024);
+    tokio::spawn({
+        let plugin_servers = plugin_servers.clone();
+        let stdout = stdout.clone();
+        async move {
+            let mut lines = BufReader::new(tokio::io::stdin()).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(reply) = plugin_servers.route(&line) {
+                    let stdout = stdout.clone();
+                    tokio::spawn(async move {
+                        if let Some(reply) = reply.await {
+                            write_line(&stdout, &reply).await;
+                        }
+                    });
+                    continue;
+                }
+                let line = calls.intercept(&line);
+                if is_initialized(&line) {
+                    tokio::spawn(start_plugin_servers(
+                        cache.clone(),
+                        plugin_servers.clone(),
+                        calls.clone(),
+                    ));
+                }
+                if input.write_all(line.as_bytes()).await.is_err()
+                    || input.write_all(b"\n").await.is_err()
+                {
+                    break;
+                }
+            }
+        }
+    });
+
+    // Copy the server's output to stdout, adding plugin tools to tool lists.
+    let (server_output, output) = tokio::io::duplex(64 * 1024);
+    let output = tokio::spawn(async move {
+        let mut lines = BufReader::new(output).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if !write_line(&stdout, &plugin_servers.extend_response(line)).await {
+                break;
+            }
+        }
+    });
+
+    let stdio = ByteStreams::new(server_output.compat_write(), server_input.compat());
+    server.connect_to(stdio).await?;
+    let _ = output.await;
+    Ok(())
+}
+
+/// Write one message line to stdout; false if stdout is closed.
+async fn write_line(stdout: &tokio::sync::Mutex<tokio::io::Stdout>, line: &str) -> bool {
+    let mut stdout = stdout.lock().await;
+    stdout.write_all(line.as_bytes()).await.is_ok()
+        && stdout.write_all(b"\n").await.is_ok()
+        && stdout.flush().await.is_ok()
+}
+
+/// Whether `line` is the client's `notifications/initialized`.
+fn is_initialized(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line).is_ok_and(|message| {
+        message.get("method").and_then(|m| m.as_str()) == Some("notifications/initialized")
+    })
+}
+
+/// Send `notifications/resources/list_changed` to the client each time the
+/// cached registry is reloaded, until the client connection closes.
+async fn notify_skill_changes(
+    cache: Arc<ServerCache>,
+    client: tokio::sync::mpsc::UnboundedSender<String>,
+) {
+    let mut changes = cache.registry_changes();
+    loop {
+        tokio::select! {
+            changed = changes.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+            }
+            () = client.closed() => return,
+        }
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/list_changed",
+        });
+        if client.send(notification.to_string()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Start the plugin MCP servers that apply to the client's workspace, chosen
+/// like a tool call's (see [`workspace_root`]), once the client has
+/// initialized. The servers start in the background.
+async fn start_plugin_servers(
+    cache: Arc<ServerCache>,
+    plugin_servers: Arc<PluginServers>,
+    calls: Arc<ToolCalls>,
+) {
+    let roots = calls
+        .roots
+        .get_with(plugin_servers.request_client("roots/list", serde_json::json!({})))
+        .await;
+    let root = match pick_workspace_root(&roots) {
+        Some(root) => root,
+        None => match std::env::current_dir() {
+            Ok(cwd) => cwd,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to get cwd; not starting plugin MCP servers");
+                return;
+            }
+        },
+    };
+    let workspace = cache.workspace_deps(&root).await;
+    plugin_servers.start(&cache.registry(), &root, &workspace);
+}
+
+/// Build the Symposium MCP server. The cache may be shared between servers
+/// (e.g. one per HTTP session); `calls` must be fed every incoming message
+/// via [`ToolCalls::intercept`].
+fn build_server(
+    cache: Arc<ServerCache>,
+    calls: Arc<ToolCalls>,
+) -> McpServer<role::mcp::Client, impl RunWithConnectionTo<role::mcp::Client>> {
+    let rust_calls = calls.clone();
+    let rust_cache = cache.clone();
+    let cargo_calls = calls.clone();
+
+    McpServer::builder("symposium".to_string())
+        .instructions(
+            "Symposium — AI the Rust Way. \
+             Use the `rust` tool for Rust development guidance. \
+             Use the `crate` tool to find crate sources and guidance. \
+             Use the `cargo` tool to run cargo with compact output.",
+        )
+        .tool_fn(
+            "rust",
+            RUST_TOOL_DESCRIPTION,
+            async move |input: RustToolInput, cx: McpConnectionTo<role::mcp::Client>| {
+                let call = rust_calls.begin(input.call.as_deref(), &cx);
+                let output = call
+                    .run(async {
+                        let root = workspace_root(input.workspace, &rust_calls.roots, &cx).await?;
+                        let workspace = rust_cache.workspace_deps(&root).await;
+                        let registry = rust_cache.registry();
+
+                        let args = rust_command::split_command(&input.command);
+                        rust_command::execute(&args, &registry, &root, &workspace)
+                            .await
+                            .map_err(|e| sacp::util::internal_error(format!("{e}")))
+                    })
+                    .await?;
+                Ok(RustToolOutput { output })
+            },
+            sacp::tool_fn!(),
+        )
+        .tool_fn(
+            "cargo",
+            CARGO_TOOL_DESCRIPTION,
+            async move |input: CargoToolInput, cx: McpConnectionTo<role::mcp::Client>| {
+                let call = cargo_calls.begin(input.call.as_deref(), &cx);
+                let output = call
+                    .run(async {
+                        let root = workspace_root(input.workspace, &cargo_calls.roots, &cx).await?;
+                        let result = match input.detail {
+                            Some(index) => cargo_wrapper::detail_output(&root, index),
+                            None => cargo_wrapper::run_output(&root, &input.args).await,
+                        };
+                        result.map_err(|e| sacp::util::internal_error(format!("{e}")))
+                    })
+                    .await?;
+                Ok(CargoToolOutput { output })
+            },
+            sacp::tool_fn!(),
+        )
+        .tool_fn(
+            "crate",
+            CRATE_TOOL_DESCRIPTION,
+            async move |input: CrateToolInput, cx: McpConnectionTo<role::mcp::Client>| {
+                let call = calls.begin(input.call.as_deref(), &cx);
+                let output = call
+                    .run(async {
+                        let root = workspace_root(input.workspace, &calls.roots, &cx).await?;
+
+                        let workspace = cache.workspace_deps(&root).await;
+                        let registry = cache.registry();
+
+                        run_crate_command(input.command, &registry, &root, &workspace)
+                            .await
+                            .map_err(|e| sacp::util::internal_error(format!("{e}")))
+                    })
+                    .await?;
+
+                Ok(CrateToolOutput { output })
+            },
+            sacp::tool_fn!(),
+        )
+        .build()
+}
+
+/// Execute one `crate` tool command against a workspace.
+async fn run_crate_command(
+    command: CrateToolCommand,
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    match command {
+        CrateToolCommand::List => Ok(skills::list_output(registry, workspace).await),
+        CrateToolCommand::Info {
+            name,
+            version,
+            budget,
+            with_resources,
+        } => {
+            let options = skills::GuidanceOptions {
+                budget,
+                with_resources,
+            };
+            skills::info_output(
+                &name,
+                version.as_deref(),
+                options,
+                registry,
+                root,
+                workspace,
+            )
+            .await
+        }
+        CrateToolCommand::Search {
+            name,
+            version,
+            pattern,
+            limit,
+            context,
+        } => {
+            let mut options = crate_sources::SearchOptions::default();
+            // A limit of 0 would show nothing; treat it as unset.
+            if let Some(limit) = limit.filter(|&limit| limit > 0) {
+                options.limit = limit;
+            }
+            if let Some(context) = context {
+                options.context = context;
+            }
+            crate_sources::search_output(&name, version.as_deref(), &pattern, &options, workspace)
+                .await
+        }
+        CrateToolCommand::Read {
+            name,
+            version,
+            path,
+            start_line,
+            end_line,
+        } => {
+            crate_sources::read_output(
+                &name,
+                version.as_deref(),
+                &path,
+                start_line,
+                end_line,
+                workspace,
+            )
+            .await
+        }
+        CrateToolCommand::Tree { name, version } => {
+            crate_sources::tree_output(&name, version.as_deref(), workspace).await
+        }
+        CrateToolCommand::Api { name, version } => {
+            crate_sources::api_output(&name, version.as_deref(), workspace).await
+        }
+    }
+}
+
+// --- Rust tool ---
+
+const RUST_TOOL_DESCRIPTION: &str = "\
+Use the Symposium Rust tool for guidance on Rust best practices \
+and how to use dependencies of the current project. \
+Execute the tool with the argument `help` to learn more and to see \
+the commands available in the current workspace.";
+
+#[derive(Deserialize, JsonSchema)]
+struct RustToolInput {
+    /// The command to run with its arguments (e.g., "help", "crate serde", "explain E0502")
+    command: String,
+    /// Optional workspace directory; defaults to the client's roots
+    #[serde(default)]
+    workspace: Option<PathBuf>,
+    #[serde(default, rename = "_symposium_call")]
+    #[schemars(skip)]
+    call: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct RustToolOutput {
+    output: String,
+}
+
+// --- Cargo tool ---
+
+const CARGO_TOOL_DESCRIPTION: &str = "\
+Run cargo in the workspace and get a compact report instead of the raw output. \
+Pass `args` as the cargo subcommand and its arguments (e.g. [\"check\"], \
+[\"test\", \"--lib\"]). Diagnostics are deduplicated, truncated, and numbered, \
+similar warnings are grouped, build progress is collapsed, and test results are \
+summarized with the output of failing tests. \
+Pass `detail` with a diagnostic number to get its full text from the last run.";
+
+#[derive(Deserialize, JsonSchema)]
+struct CargoToolInput {
+    /// Cargo subcommand and arguments (e.g., ["check"], ["test", "--lib"])
+    #[serde(default)]
+    args: Vec<String>,
+    /// Show the full text of this diagnostic from the last run instead of running cargo
+    #[serde(default)]
+    detail: Option<usize>,
+    /// Optional workspace directory; defaults to the client's roots
+    #[serde(default)]
+    workspace: Option<PathBuf>,
+    #[serde(default, rename = "_symposium_call")]
+    #[schemars(skip)]
+    call: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct CargoToolOutput {
+    output: String,
+}
+
+// --- Crate tool ---
+
+const CRATE_TOOL_DESCRIPTION: &str = "\
+Find Rust crate source code and guidance. \
+Use this to inspect crate implementations, understand APIs, or debug issues.\n\n\
+Pass a `List` command to see crates where specialized guidance is available.\n\
+Pass an `Info` command with a crate name to locate its source code.\n\
+Pass a `Search` command with a crate name and a regex `pattern` to search the crate's \
+source code; matches are returned with file paths, line numbers, and context.\n\
+Pass a `Tree` command to list the files in a crate, and a `Read` command with a \
+`path` relative to the crate root (plus optional `start_line`/`end_line`) to read one.\n\
+Pass an `Api` command to get an outline of the crate's public API: modules, types, \
+traits, and function signatures with doc summaries, with `#[cfg]` gates shown.\n\n\
+If no version is given, defaults to the version used in the current workspace, \
+or the latest version on crates.io if the crate is not a dependency. \
+The workspace is the client's first root containing a `Cargo.toml`; pass `workspace` \
+with a directory path to choose one explicitly.";
+
+#[derive(Deserialize, JsonSchema)]
+struct CrateToolInput {
+    #[serde(flatten)]
+    command: CrateToolCommand,
+    /// Optional workspace directory; defaults to the client's roots
+    #[serde(default)]
+    workspace: Option<PathBuf>,
+    /// Added by the transport to identify the call (see [`calls`]).
+    #[serde(default, rename = "_symposium_call")]
+    #[schemars(skip)]
+    call: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "command")]
+enum CrateToolCommand {
+    /// List crates where specialized guidance is available
+    List,
+    /// Get info and source location for a specific crate
+    Info {
+        /// Crate name (e.g., "serde", "tokio")
+        name: String,
+        /// Optional version constraint (e.g., "1.0.3", "^1.0")
+        #[serde(default)]
+        version: Option<String>,
+        /// Approximate token limit for the guidance (default: from config, else unlimited)
+        #[serde(default)]
+        budget: Option<usize>,
+        /// Include the text of skill resource files (examples, reference docs) in the guidance
+        #[serde(default)]
+        with_resources: bool,
+    },
+    /// Search a crate's source code with a regular expression
+    Search {
+        /// Crate name (e.g., "serde", "tokio")
+        name: String,
+        /// Optional version constraint (e.g., "1.0.3", "^1.0")
+        #[serde(default)]
+        version: Option<String>,
+        /// Regular expression to search for (Rust `regex` syntax)
+        pattern: String,
+        /// Maximum number of matches to return (default: 50; 0 also means the default)
+        #[serde(default)]
+        limit: Option<usize>,
+        /// Lines of context to show around each match (default: 2)
+        #[serde(default)]
+        context: Option<usize>,
+    },
+    /// Read a file from a crate's source code
+    Read {
+        /// Crate name (e.g., "serde", "tokio")
+        name: String,
+        /// Optional version constraint (e.g., "1.0.3", "^1.0")
+        #[serde(default)]
+        version: Option<String>,
+        /// File path relative to the crate root (e.g., "src/lib.rs")
+        path: String,
+        /// First line to read, 1-based (default: 1)
+        #[serde(default)]
+        start_line: Option<usize>,
+        /// Last line to read, inclusive (default: end of file)
+        #[serde(default)]
+        end_line: Option<usize>,
+    },
+    /// List the files in a crate's source code
+    Tree {
+        /// Crate name (e.g., "serde", "tokio")
+        name: String,
+        /// Optional version constraint (e.g., "1.0.3", "^1.0")
+        #[serde(default)]
+        version: Option<String>,
+    },
+    /// Outline the public API of a crate
+    Api {
+        /// Crate name (e.g., "serde", "tokio")
+        name: String,
+        /// Optional version constraint (e.g., "1.0.3", "^1.0")
+        #[serde(default)]
+        version: Option<String>,
+    },
+}
+
+#[derive(Serialize, JsonSchema)]
+struct CrateToolOutput {
+    output: String,
+}
+
+// --- Workspace ---
+
+/// Determine the workspace directory for a tool call.
+///
+/// An explicit `workspace` argument wins. Otherwise the client is asked for
+/// its roots, so one server process can serve several projects; if the
+/// client does not support roots, fall back to the server's working directory.
+async fn workspace_root(
+    explicit: Option<PathBuf>,
+    roots: &ClientRoots,
+    cx: &McpConnectionTo<role::mcp::Client>,
+) -> Result<PathBuf, sacp::Error> {
+    if let Some(dir) = explicit {
+        if !dir.is_dir() {
+            return Err(sacp::util::internal_error(format!(
+                "workspace `{}` is not a directory",
+                dir.display()
+            )));
+        }
+        return Ok(dir);
+    }
+
+    if let Some(root) = pick_workspace_root(&roots.get(cx).await) {
+        return Ok(root);
+    }
+
+    std::env::current_dir()
+        .map_err(|e| sacp::util::internal_error(format!("failed to get cwd: {e}")))
+}
+
+/// Prefer the first root that is a Cargo project; otherwise the first root.
+fn pick_workspace_root(roots: &[PathBuf]) -> Option<PathBuf> {
+    roots
+        .iter()
+        .find(|root| is_cargo_project(root))
+        .or_else(|| roots.first())
+        .cloned()
+}
+
+fn is_cargo_project(dir: &Path) -> bool {
+    dir.join("Cargo.toml").is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_prefers_cargo_project() {
+        let plain = tempfile::tempdir().unwrap();
+        let cargo = tempfile::tempdir().unwrap();
+        std::fs::write(cargo.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let roots = vec![plain.path().to_path_buf(), cargo.path().to_path_buf()];
+        assert_eq!(
+            pick_workspace_root(&roots),
+            Some(cargo.path().to_path_buf())
+        );
+        assert_eq!(
+            pick_workspace_root(&roots[..1]),
+            Some(plain.path().to_path_buf())
+        );
+        assert_eq!(pick_workspace_root(&[]), None);
+    }
+}
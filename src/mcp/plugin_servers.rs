@@ -0,0 +1,674 @@
+//! Tools from MCP servers declared by plugins
+//!
+//! Plugins can declare `[[mcp-servers]]`. Each client connection has its own
+//! [`PluginServers`]. Once the client has initialized, the transport starts
+//! the servers that apply to the client's workspace (see
+//! [`super::start_plugin_servers`]) in the background and in parallel. As each
+//! server lists its tools, they are offered as `<server>__<tool>` and the
+//! client is sent `notifications/tools/list_changed`.
+//!
+//! The transports hand every incoming message to [`PluginServers::route`],
+//! which takes over `tools/call` requests for those names, cancellations of
+//! those calls, and responses to [`PluginServers::request_client`]. Every
+//! outgoing message passes through [`PluginServers::extend_response`], which
+//! adds the tools to `tools/list` responses. Messages for the client, such as
+//! the servers' progress notifications, go to the channel given to
+//! [`PluginServers::new`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::plugins::{ParsedPlugin, PluginMcpServer, PluginRegistry};
+
+/// Separates the server name from the tool name in re-exported tools.
+const SEPARATOR: &str = "__";
+
+/// How long a server gets to start and list its tools.
+const START_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Protocol version sent in `initialize`.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// The plugin MCP servers running for one client connection.
+pub struct PluginServers {
+    /// Messages for the client, one per line.
+    client: mpsc::UnboundedSender<String>,
+    /// The tools of the servers started so far.
+    offered: Mutex<OfferedTools>,
+    /// Forwarded calls in flight, by the client's request id: the server
+    /// handling each and the request id used with it.
+    calls: Mutex<HashMap<String, (Arc<Connection>, u64)>>,
+    /// Requests sent to the client, by id.
+    client_requests: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+    next_client_request: AtomicU64,
+}
+
+#[derive(Default)]
+struct OfferedTools {
+    /// Re-exported tool definitions, with prefixed names.
+    tools: Vec<Value>,
+    /// Prefixed tool name to the server providing it and its original name.
+    routes: HashMap<String, (Arc<Connection>, String)>,
+}
+
+impl PluginServers {
+    /// No servers yet; messages for the client are sent to `client`.
+    pub fn new(client: mpsc::UnboundedSender<String>) -> Arc<Self> {
+        Arc::new(Self {
+            client,
+            offered: Mutex::default(),
+            calls: Mutex::default(),
+            client_requests: Mutex::default(),
+            next_client_request: AtomicU64::new(1),
+        })
+    }
+
+    /// Start every plugin MCP server that applies to the workspace, each in
+    /// its own task. A server's tools are offered once it has listed them.
+    ///
+    /// Servers that fail to start are logged and left out.
+    pub fn start(
+        self: &Arc<Self>,
+        registry: &PluginRegistry,
+        root: &Path,
+        workspace: &[(String, semver::Version)],
+    ) {
+        let mut names = Vec::new();
+        for plugin in &registry.plugins {
+            for server in &plugin.plugin.mcp_servers {
+                if !server.applies_to(workspace) {
+                    continue;
+                }
+                if names.contains(&server.name) {
+                    tracing::warn!(plugin = %plugin.plugin.name, server = %server.name, "duplicate MCP server name; skipping");
+                    continue;
+                }
+                names.push(server.name.clone());
+
+                tokio::spawn(start_server(
+                    Arc::downgrade(self),
+                    plugin.clone(),
+                    server.clone(),
+                    root.to_path_buf(),
+                ));
+            }
+        }
+    }
+
+    /// Offer a server's tools under its name, and tell the client.
+    fn add(&self, server: &str, connection: Arc<Connection>, tools: Vec<Value>) {
+        tracing::info!(%server, tools = tools.len(), "plugin MCP server started");
+        {
+            let mut offered = self.offered.lock().unwrap();
+            for mut tool in tools {
+                let Some(name) = tool.get("name").and_then(Value::as_str).map(str::to_string)
+                else {
+                    continue;
+                };
+                let prefixed = format!("{server}{SEPARATOR}{name}");
+                tool["name"] = json!(prefixed);
+                offered.tools.push(tool);
+                offered.routes.insert(prefixed, (connection.clone(), name));
+            }
+        }
+        let changed = json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" });
+        let _ = self.client.send(changed.to_string());
+    }
+
+    /// Handle `line` if it is meant for the plugin servers, returning a
+    /// future that resolves to the reply line, if any:
+    ///
+    /// * a `tools/call` request for a plugin tool is forwarded to its server;
+    /// * a `notifications/cancelled` for such a call is forwarded too, and
+    ///   the call is not answered;
+    /// * a response to [`request_client`](Self::request_client) is delivered.
+    ///
+    /// Other messages return `None` and go to the Symposium server as usual.
+    pub fn route(self: &Arc<Self>, line: &str) -> Option<BoxFuture<'static, Option<String>>> {
+        let message: Value = serde_json::from_str(line).ok()?;
+        match message.get("method").and_then(Value::as_str) {
+            Some("tools/call") => {
+                let id = message.get("id")?.clone();
+                let mut params = message.get("params")?.clone();
+                let name = params.get("name")?.as_str()?;
+                let (connection, tool) = self.offered.lock().unwrap().routes.get(name)?.clone();
+                params["name"] = json!(tool);
+
+                let key = id.to_string();
+                let server_id = connection.next_id();
+                self.calls
+                    .lock()
+                    .unwrap()
+                    .insert(key.clone(), (connection.clone(), server_id));
+                let servers = self.clone();
+                Some(
+                    async move {
+                        let response = connection.request_as(server_id, "tools/call", params).await;
+                        // A cancelled call was removed already and gets no reply.
+                        servers.calls.lock().unwrap().remove(&key)?;
+                        let response = response.unwrap_or_else(
+                            |e| json!({ "error": { "code": -32603, "message": format!("{e}") } }),
+                        );
+                        let mut reply = json!({ "jsonrpc": "2.0", "id": id });
+                        for key in ["result", "error"] {
+                            if let Some(value) = response.get(key) {
+                                reply[key] = value.clone();
+                            }
+                        }
+                        Some(reply.to_string())
+                    }
+                    .boxed(),
+                )
+            }
+            Some("notifications/cancelled") => {
+                let key = message.pointer("/params/requestId")?.to_string();
+                let (connection, server_id) = self.calls.lock().unwrap().remove(&key)?;
+                let reason = message.pointer("/params/reason").cloned();
+                Some(
+                    async move {
+                        connection.cancel(server_id, reason).await;
+                        None
+                    }
+                    .boxed(),
+                )
+            }
+            Some(_) => None,
+            None => {
+                let id = message.get("id")?.as_str()?;
+                let sender = self.client_requests.lock().unwrap().remove(id)?;
+                let _ = sender.send(message);
+                Some(futures::future::ready(None).boxed())
+            }
+        }
+    }
+
+    /// Send a request to the client when the returned future is first
+    /// polled, and resolve to the result of its response. The transport must
+    /// pass the client's messages to [`route`](Self::route).
+    pub fn request_client(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> impl Future<Output = Result<Value>> + Send + 'static {
+        let id = format!(
+            "symposium-{}",
+            self.next_client_request.fetch_add(1, Ordering::Relaxed)
+        );
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let method = method.to_string();
+        let (tx, rx) = oneshot::channel();
+        let client = self.client.clone();
+        let pending = self.client_requests.lock().unwrap().insert(id, tx);
+        debug_assert!(pending.is_none());
+        async move {
+            client
+                .send(message.to_string())
+                .map_err(|_| anyhow!("client connection closed"))?;
+            let response = rx
+                .await
+                .map_err(|_| anyhow!("client did not answer `{method}`"))?;
+            result(response)
+        }
+    }
+
+    /// Add the plugin tools to a `tools/list` response from the Symposium
+    /// server, and declare `tools.listChanged` and `resources.listChanged`
+    /// in its `initialize` response; other messages are returned unchanged.
+    pub fn extend_response(&self, line: String) -> String {
+        let Ok(mut message) = serde_json::from_str::<Value>(&line) else {
+            return line;
+        };
+        if message.get("method").is_some() {
+            return line;
+        }
+        if let Some(result) = message.get_mut("result")
+            && result.get("protocolVersion").is_some()
+            && let Some(capabilities) = result
+                .get_mut("capabilities")
+                .and_then(Value::as_object_mut)
+        {
+            for capability in ["tools", "resources"] {
+                let entry = capabilities.entry(capability).or_insert_with(|| json!({}));
+                if let Some(entry) = entry.as_object_mut() {
+                    entry.insert("listChanged".to_string(), json!(true));
+                }
+            }
+            return message.to_string();
+        }
+        let offered = self.offered.lock().unwrap();
+        if offered.tools.is_empty() {
+            return line;
+        }
+        let Some(tools) = message
+            .pointer_mut("/result/tools")
+            .and_then(Value::as_array_mut)
+        else {
+            return line;
+        };
+        tools.extend(offered.tools.iter().cloned());
+        message.to_string()
+    }
+}
+
+/// Start one server and offer its tools, unless the client connection is
+/// gone by then.
+async fn start_server(
+    servers: Weak<PluginServers>,
+    plugin: ParsedPlugin,
+    server: PluginMcpServer,
+    root: PathBuf,
+) {
+    let Some(client) = servers.upgrade().map(|servers| servers.client.clone()) else {
+        return;
+    };
+    let started = tokio::time::timeout(START_TIMEOUT, spawn(&plugin, &server, &root, client)).await;
+    match started {
+        Ok(Ok((connection, tools))) => {
+            if let Some(servers) = servers.upgrade() {
+                servers.add(&server.name, connection, tools);
+            }
+        }
+        Ok(Err(e)) => {
+            tracing::warn!(plugin = %plugin.plugin.name, server = %server.name, error = %e, "failed to start plugin MCP server")
+        }
+        Err(_) => {
+            tracing::warn!(plugin = %plugin.plugin.name, server = %server.name, "timed out starting plugin MCP server")
+        }
+    }
+}
+
+/// Spawn a plugin MCP server, initialize it, and list its tools. Its
+/// progress notifications are sent to `client`.
+async fn spawn(
+    plugin: &ParsedPlugin,
+    server: &PluginMcpServer,
+    root: &Path,
+    client: mpsc::UnboundedSender<String>,
+) -> Result<(Arc<Connection>, Vec<Value>)> {
+    let plugin_dir = plugin.path.parent().unwrap_or(Path::new("."));
+    let mut child = tokio::process::Command::new(&server.command)
+        .args(&server.args)
+        .current_dir(root)
+        .env("SYMPOSIUM_PLUGIN_DIR", plugin_dir)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to run `{}`", server.command))?;
+    let stdin = child.stdin.take().context("server stdin unavailable")?;
+    let stdout = child.stdout.take().context("server stdout unavailable")?;
+
+    let connection = Connection::new(stdin, BufReader::new(stdout), client, Some(child));
+    let tools = connection.initialize().await?;
+    Ok((connection, tools))
+}
+
+/// A client connection to one plugin MCP server.
+struct Connection {
+    input: tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+    next_id: AtomicU64,
+    /// Where the server's progress notifications go.
+    client: mpsc::UnboundedSender<String>,
+    /// Killed when the connection is dropped.
+    _child: Option<tokio::process::Child>,
+}
+
+impl Connection {
+    fn new(
+        input: impl AsyncWrite + Send + Unpin + 'static,
+        output: impl AsyncBufRead + Send + Unpin + 'static,
+        client: mpsc::UnboundedSender<String>,
+        child: Option<tokio::process::Child>,
+    ) -> Arc<Self> {
+        let connection = Arc::new(Self {
+            input: tokio::sync::Mutex::new(Box::new(input)),
+            pending: Mutex::default(),
+            next_id: AtomicU64::new(1),
+            client,
+            _child: child,
+        });
+        tokio::spawn(read_messages(Arc::downgrade(&connection), output));
+        connection
+    }
+
+    /// Perform the MCP handshake and return the server's tools.
+    async fn initialize(&self) -> Result<Vec<Value>> {
+        let params = json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "symposium", "version": env!("CARGO_PKG_VERSION") },
+        });
+        result(self.request("initialize", params).await?)?;
+        self.send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await?;
+
+        let mut tools = Vec::new();
+        let mut cursor = None;
+        loop {
+            let params = match cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let page = result(self.request("tools/list", params).await?)?;
+            if let Some(page_tools) = page.get("tools").and_then(Value::as_array) {
+                tools.extend(page_tools.iter().cloned());
+            }
+            cursor = page
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Send a request and return the whole response message.
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.request_as(self.next_id(), method, params).await
+    }
+
+    /// Send a request with the given id, from [`next_id`](Self::next_id).
+    async fn request_as(&self, id: u64, method: &str, params: Value) -> Result<Value> {
+        let (tx, rx) = oneshot::channel();
+        // Register before sending so the response cannot arrive first.
+        self.pending.lock().unwrap().insert(id, tx);
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        rx.await
+            .map_err(|_| anyhow!("MCP server exited before answering `{method}`"))
+    }
+
+    /// Cancel the request `id`: it fails at once, and the server is told.
+    async fn cancel(&self, id: u64, reason: Option<Value>) {
+        self.pending.lock().unwrap().remove(&id);
+        let mut params = json!({ "requestId": id });
+        if let Some(reason) = reason {
+            params["reason"] = reason;
+        }
+        let notification =
+            json!({ "jsonrpc": "2.0", "method": "notifications/cancelled", "params": params });
+        if let Err(e) = self.send(&notification).await {
+            tracing::debug!(error = %e, "failed to cancel plugin MCP server request");
+        }
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        let mut input = self.input.lock().await;
+        input.write_all(format!("{message}\n").as_bytes()).await?;
+        input.flush().await?;
+        Ok(())
+    }
+}
+
+/// Deliver responses from a server to their requests, pass its progress
+/// notifications to the client, and answer its own requests (only `ping` is
+/// supported). Stops when the server's output ends
+/// or the connection is dropped.
+async fn read_messages(connection: Weak<Connection>, output: impl AsyncBufRead + Unpin) {
+    let mut lines = output.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Some(connection) = connection.upgrade() else {
+            return;
+        };
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            tracing::debug!(%line, "plugin MCP server sent invalid JSON");
+            continue;
+        };
+        let id = message.get("id").cloned();
+        match (message.get("method").and_then(Value::as_str), id) {
+            (None, Some(id)) => {
+                let sender = id
+                    .as_u64()
+                    .and_then(|id| connection.pending.lock().unwrap().remove(&id));
+                if let Some(sender) = sender {
+                    let _ = sender.send(message);
+                }
+            }
+            (Some(method), Some(id)) => {
+                let reply = if method == "ping" {
+                    json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+                } else {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": format!("method `{method}` not supported") },
+                    })
+                };
+                if connection.send(&reply).await.is_err() {
+                    return;
+                }
+            }
+            (Some("notifications/progress"), None) => {
+                let _ = connection.client.send(line);
+            }
+            (Some(method), None) => tracing::debug!(%method, "plugin MCP server notification"),
+            (None, None) => {}
+        }
+    }
+    // Fail any requests still waiting.
+    if let Some(connection) = connection.upgrade() {
+        connection.pending.lock().unwrap().clear();
+    }
+}
+
+/// The `result` of a response, or its error as an `Err`.
+fn result(response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        bail!("{message}");
+    }
+    Ok(response.get("result").cloned().unwrap_or(Value::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::DuplexStream;
+
+    /// A minimal MCP server with an `echo` tool, which reports progress when
+    /// asked to, and a `wait` tool, which never answers. Every message it
+    /// receives is sent to `seen`.
+    async fn stub_server(
+        input: DuplexStream,
+        mut output: DuplexStream,
+        seen: mpsc::UnboundedSender<Value>,
+    ) {
+        let mut lines = BufReader::new(input).lines();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let message: Value = serde_json::from_str(&line).unwrap();
+            let _ = seen.send(message.clone());
+            let mut replies = Vec::new();
+            let result = match message["method"].as_str().unwrap() {
+                "initialize" => json!({ "protocolVersion": PROTOCOL_VERSION, "capabilities": {} }),
+                "tools/list" => json!({ "tools": [
+                    { "name": "echo", "inputSchema": { "type": "object" } },
+                    { "name": "wait", "inputSchema": { "type": "object" } },
+                ] }),
+                "tools/call" if message["params"]["name"] == "echo" => {
+                    if let Some(token) = message.pointer("/params/_meta/progressToken") {
+                        replies.push(json!({
+                            "jsonrpc": "2.0",
+                            "method": "notifications/progress",
+                            "params": { "progressToken": token, "progress": 1 },
+                        }));
+                    }
+                    json!({
+                        "content": [{ "type": "text", "text": message["params"]["arguments"]["text"] }],
+                    })
+                }
+                _ => continue,
+            };
+            replies.push(json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }));
+            for reply in replies {
+                output
+                    .write_all(format!("{reply}\n").as_bytes())
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    struct Stub {
+        servers: Arc<PluginServers>,
+        /// Messages for the client.
+        client: mpsc::UnboundedReceiver<String>,
+        /// Messages the stub server received.
+        seen: mpsc::UnboundedReceiver<Value>,
+    }
+
+    async fn servers() -> Stub {
+        let (input, server_in) = tokio::io::duplex(64 * 1024);
+        let (server_out, output) = tokio::io::duplex(64 * 1024);
+        let (seen_tx, seen) = mpsc::unbounded_channel();
+        tokio::spawn(stub_server(server_in, server_out, seen_tx));
+
+        let (client_tx, client) = mpsc::unbounded_channel();
+        let connection = Connection::new(input, BufReader::new(output), client_tx.clone(), None);
+        let tools = connection.initialize().await.unwrap();
+        let servers = PluginServers::new(client_tx);
+        servers.add("stub", connection, tools);
+        Stub {
+            servers,
+            client,
+            seen,
+        }
+    }
+
+    #[tokio::test]
+    async fn plugin_tools_are_listed_with_a_prefix() {
+        let mut stub = servers().await;
+        let changed: Value = serde_json::from_str(&stub.client.recv().await.unwrap()).unwrap();
+        assert_eq!(changed["method"], "notifications/tools/list_changed");
+
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "tools": [{ "name": "rust" }] },
+        });
+        let extended: Value =
+            serde_json::from_str(&stub.servers.extend_response(response.to_string())).unwrap();
+        let names: Vec<_> = extended["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["rust", "stub__echo", "stub__wait"]);
+
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": { "protocolVersion": PROTOCOL_VERSION, "capabilities": { "tools": {} } },
+        });
+        let extended: Value =
+            serde_json::from_str(&stub.servers.extend_response(initialize.to_string())).unwrap();
+        assert_eq!(
+            extended["result"]["capabilities"]["tools"]["listChanged"],
+            true
+        );
+        assert_eq!(
+            extended["result"]["capabilities"]["resources"]["listChanged"],
+            true
+        );
+    }
+
+    #[tokio::test]
+    async fn plugin_tool_calls_are_forwarded() {
+        let mut stub = servers().await;
+        stub.client.recv().await.unwrap();
+
+        let own_tool = json!({
+            "jsonrpc": "2.0", "id": 7, "method": "tools/call",
+            "params": { "name": "rust", "arguments": {} },
+        });
+        assert!(stub.servers.route(&own_tool.to_string()).is_none());
+
+        let plugin_tool = json!({
+            "jsonrpc": "2.0", "id": 8, "method": "tools/call",
+            "params": {
+                "name": "stub__echo",
+                "arguments": { "text": "hi" },
+                "_meta": { "progressToken": "p" },
+            },
+        });
+        let reply = stub.servers.route(&plugin_tool.to_string()).unwrap().await;
+        let reply: Value = serde_json::from_str(&reply.unwrap()).unwrap();
+        assert_eq!(
+            reply,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 8,
+                "result": { "content": [{ "type": "text", "text": "hi" }] },
+            })
+        );
+        let progress: Value = serde_json::from_str(&stub.client.recv().await.unwrap()).unwrap();
+        assert_eq!(
+            progress["params"],
+            json!({ "progressToken": "p", "progress": 1 })
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_calls_are_forwarded() {
+        let mut stub = servers().await;
+        let call = json!({
+            "jsonrpc": "2.0", "id": "c", "method": "tools/call",
+            "params": { "name": "stub__wait", "arguments": {} },
+        });
+        let reply = tokio::spawn(stub.servers.route(&call.to_string()).unwrap());
+        let forwarded = loop {
+            let message = stub.seen.recv().await.unwrap();
+            if message["method"] == "tools/call" {
+                break message;
+            }
+        };
+
+        let cancel = json!({
+            "jsonrpc": "2.0", "method": "notifications/cancelled",
+            "params": { "requestId": "c", "reason": "user" },
+        });
+        assert_eq!(stub.servers.route(&cancel.to_string()).unwrap().await, None);
+        assert_eq!(reply.await.unwrap(), None);
+        assert_eq!(
+            stub.seen.recv().await.unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/cancelled",
+                "params": { "requestId": forwarded["id"], "reason": "user" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn client_requests_get_their_responses() {
+        let (client_tx, mut client) = mpsc::unbounded_channel();
+        let servers = PluginServers::new(client_tx);
+        let request = tokio::spawn(servers.request_client("roots/list", json!({})));
+
+        let sent: Value = serde_json::from_str(&client.recv().await.unwrap()).unwrap();
+        assert_eq!(sent["method"], "roots/list");
+        let response = json!({ "jsonrpc": "2.0", "id": sent["id"], "result": { "roots": [] } });
+        assert!(servers.route(&response.to_string()).is_some());
+        assert_eq!(request.await.unwrap().unwrap(), json!({ "roots": [] }));
+    }
+}
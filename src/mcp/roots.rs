@@ -0,0 +1,165 @@
+//! The client's roots, requested once and cached per connection
+//!
+//! Tools resolve their workspace from the client's roots. The client is only
+//! asked (`roots/list`) if it declared the `roots` capability in `initialize`,
+//! and the answer is kept until it sends `notifications/roots/list_changed`.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sacp::UntypedMessage;
+use sacp::mcp_server::McpConnectionTo;
+use sacp::role;
+use serde::Deserialize;
+
+/// How long to wait for the client to answer a `roots/list` request.
+const ROOTS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What is known about one client's roots.
+#[derive(Default)]
+pub struct ClientRoots {
+    state: Mutex<RootsState>,
+}
+
+#[derive(Default)]
+struct RootsState {
+    /// Whether the client declared the `roots` capability.
+    supported: bool,
+    /// Roots from the last `roots/list`, until the client reports a change.
+    cached: Option<Vec<PathBuf>>,
+    /// Bumped on every change, so a stale answer is not cached.
+    generation: u64,
+}
+
+impl ClientRoots {
+    /// Note an `initialize` request or a roots change notification.
+    pub fn observe(&self, method: &str, message: &serde_json::Value) {
+        let mut state = self.state.lock().unwrap();
+        match method {
+            "initialize" => {
+                state.supported = message
+                    .pointer("/params/capabilities/roots")
+                    .is_some_and(|roots| roots.is_object());
+                state.cached = None;
+                state.generation += 1;
+            }
+            "notifications/roots/list_changed" => {
+                state.cached = None;
+                state.generation += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// The client's local root directories; empty if it does not support roots.
+    pub async fn get(&self, cx: &McpConnectionTo<role::mcp::Client>) -> Vec<PathBuf> {
+        self.get_with(async {
+            let request = UntypedMessage::new("roots/list", serde_json::json!({}))?;
+            cx.connection_to().send_request(request).block_task().await
+        })
+        .await
+    }
+
+    /// Like [`get`](Self::get), asking the client with `request`, a future
+    /// that sends `roots/list` and resolves to its result. It is only polled
+    /// when the roots are not cached.
+    pub async fn get_with<E: std::fmt::Display>(
+        &self,
+        request: impl Future<Output = Result<serde_json::Value, E>>,
+    ) -> Vec<PathBuf> {
+        let generation = {
+            let state = self.state.lock().unwrap();
+            if !state.supported {
+                return Vec::new();
+            }
+            if let Some(roots) = &state.cached {
+                return roots.clone();
+            }
+            state.generation
+        };
+
+        let roots = match tokio::time::timeout(ROOTS_TIMEOUT, request).await {
+            Ok(Ok(value)) => parse_roots(&value),
+            Ok(Err(e)) => {
+                tracing::debug!(error = %e, "client did not provide roots");
+                Vec::new()
+            }
+            Err(_) => {
+                tracing::debug!("timed out waiting for roots/list");
+                Vec::new()
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.generation == generation {
+            state.cached = Some(roots.clone());
+        }
+        roots
+    }
+}
+
+#[derive(Deserialize)]
+struct ListRootsResult {
+    roots: Vec<Root>,
+}
+
+#[derive(Deserialize)]
+struct Root {
+    uri: String,
+}
+
+/// Convert a `roots/list` result into local paths, ignoring non-`file://` roots.
+fn parse_roots(value: &serde_json::Value) -> Vec<PathBuf> {
+    let Ok(result) = serde_json::from_value::<ListRootsResult>(value.clone()) else {
+        return Vec::new();
+    };
+    result
+        .roots
+        .iter()
+        .filter_map(|root| url::Url::parse(&root.uri).ok()?.to_file_path().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_roots_keeps_file_uris() {
+        let value = serde_json::json!({
+            "roots": [
+                { "uri": "file:///home/user/my%20project", "name": "project" },
+                { "uri": "https://example.com/repo" },
+            ]
+        });
+        assert_eq!(
+            parse_roots(&value),
+            vec![PathBuf::from("/home/user/my project")]
+        );
+    }
+
+    #[test]
+    fn observe_tracks_capability_and_changes() {
+        let roots = ClientRoots::default();
+        roots.observe(
+            "initialize",
+            &serde_json::json!({"params": {"capabilities": {"roots": {"listChanged": true}}}}),
+        );
+        {
+            let mut state = roots.state.lock().unwrap();
+            assert!(state.supported);
+            state.cached = Some(vec![PathBuf::from("/work")]);
+        }
+
+        roots.observe("notifications/roots/list_changed", &serde_json::json!({}));
+        assert!(roots.state.lock().unwrap().cached.is_none());
+
+        roots.observe(
+            "initialize",
+            &serde_json::json!({"params": {"capabilities": {}}}),
+        );
+        assert!(!roots.state.lock().unwrap().supported);
+    }
+}
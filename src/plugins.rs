@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
 
+use crate::config::Precedence;
 use crate::git_source::UpdateLevel;
 use crate::hook::HookEvent;
 
@@ -19,9 +23,10 @@ pub struct PluginSource {
 
 /// A `[[skills]]` entry from a plugin manifest.
 ///
-/// Each group declares which crates it advises on (`crates`), workspace
-/// constraints (`applies-when`), an activation mode, and optionally a remote
-/// source for the skill files.
+/// Each group declares which crates it advises on (`crates`) or, for general
+/// Rust guidance, which `topics` it covers; workspace constraints
+/// (`applies-when`), an activation mode, and optionally a remote source for
+/// the skill files.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SkillGroup {
     /// Crate predicates this group advises on (e.g., `"serde"` or `["serde", "serde_json>=1.0"]`).
@@ -32,6 +37,10 @@ pub struct SkillGroup {
     pub applies_when: Option<Vec<crate::predicate::Predicate>>,
     /// Activation mode for skills in this group.
     pub activation: Option<crate::skills::Activation>,
+    /// Topics for language-level skills. A group with `topics` and no
+    /// `crates` holds general Rust guidance rather than crate guidance.
+    #[serde(default)]
+    pub topics: Option<Vec<String>>,
     /// Remote source for skills.
     #[serde(default)]
     pub source: PluginSource,
@@ -89,6 +98,9 @@ pub struct ParsedPlugin {
 
     /// The parsed plugin manifest.
     pub plugin: Plugin,
+
+    /// Precedence of the plugin source it was loaded from.
+    pub precedence: Precedence,
 }
 
 /// A loaded plugin manifest with hooks and skill groups.
@@ -102,6 +114,11 @@ pub struct Plugin {
     pub installation: Option<Installation>,
     pub hooks: Vec<Hook>,
     pub skills: Vec<SkillGroup>,
+    pub commands: Vec<RustCommand>,
+    pub mcp_servers: Vec<PluginMcpServer>,
+    /// Crates whose guidance also applies to a crate, by crate name (e.g.
+    /// `serde_json` to `["serde"]`).
+    pub related: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -109,6 +126,64 @@ pub struct Installation {
     pub commands: Vec<String>,
 }
 
+/// A `[[commands]]` entry: a `rust` subcommand provided by a plugin.
+///
+/// `symposium rust <name> [args...]` runs `command` through `sh -c` in the
+/// workspace root, with the remaining arguments appended, and returns its
+/// standard output.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RustCommand {
+    /// Subcommand name (a single word, e.g. `"audit"`).
+    pub name: String,
+    /// One-line description shown by `rust help`.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Shell command to run.
+    pub command: String,
+    /// Workspace constraints: the command is only available when all match.
+    #[serde(default, rename = "applies-when")]
+    pub applies_when: Option<Vec<crate::predicate::Predicate>>,
+}
+
+impl RustCommand {
+    /// Check whether this command is available in the given workspace.
+    pub fn applies_to(&self, workspace: &[(String, semver::Version)]) -> bool {
+        self.applies_when
+            .iter()
+            .flatten()
+            .all(|p| p.matches(workspace))
+    }
+}
+
+/// An `[[mcp-servers]]` entry: an MCP server whose tools `symposium mcp`
+/// re-exports.
+///
+/// The server is spawned over stdio in the workspace root, and each of its
+/// tools is offered as `<name>__<tool>`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginMcpServer {
+    /// Server name, used as the tool name prefix (e.g. `"sqlx"`).
+    pub name: String,
+    /// Program to run.
+    pub command: String,
+    /// Arguments passed to the program.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Workspace constraints: the server is only started when all match.
+    #[serde(default, rename = "applies-when")]
+    pub applies_when: Option<Vec<crate::predicate::Predicate>>,
+}
+
+impl PluginMcpServer {
+    /// Check whether this server applies to the given workspace.
+    pub fn applies_to(&self, workspace: &[(String, semver::Version)]) -> bool {
+        self.applies_when
+            .iter()
+            .flatten()
+            .all(|p| p.matches(workspace))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Hook {
     pub name: String,
@@ -141,7 +216,41 @@ pub struct PluginRegistry {
     pub plugins: Vec<ParsedPlugin>,
     /// Skills discovered as standalone directories containing a `SKILL.md`
     /// file directly in a plugin source directory (no TOML manifest needed).
-    pub standalone_skills: Vec<crate::skills::Skill>,
+    pub standalone_skills: Vec<StandaloneSkill>,
+}
+
+impl PluginRegistry {
+    /// Crates declared as related to `crate_name` by any plugin, in plugin
+    /// order and without repeats.
+    pub fn related_crates(&self, crate_name: &str) -> Vec<String> {
+        let mut related: Vec<String> = Vec::new();
+        for parsed in &self.plugins {
+            for name in parsed.plugin.related.get(crate_name).into_iter().flatten() {
+                if name != crate_name && !related.contains(name) {
+                    related.push(name.clone());
+                }
+            }
+        }
+        related
+    }
+}
+
+/// A standalone skill with the plugin source it was found in.
+#[derive(Debug)]
+pub struct StandaloneSkill {
+    /// Name of the plugin source; stands in for the plugin name when the
+    /// skill is referred to as `<plugin>/<skill>`.
+    pub source: String,
+    /// Precedence of that source.
+    pub precedence: Precedence,
+    pub skill: crate::skills::Skill,
+}
+
+/// A plugin source directory on disk.
+struct SourceDir {
+    name: String,
+    dir: PathBuf,
+    precedence: Precedence,
 }
 
 /// Raw scan results from a plugin source directory.
@@ -161,6 +270,12 @@ struct PluginManifest {
     hooks: Vec<Hook>,
     #[serde(default)]
     skills: Vec<SkillGroup>,
+    #[serde(default)]
+    commands: Vec<RustCommand>,
+    #[serde(default, rename = "mcp-servers")]
+    mcp_servers: Vec<PluginMcpServer>,
+    #[serde(default)]
+    related: BTreeMap<String, Vec<String>>,
 }
 
 /// Fetch/update git-based plugin sources.
@@ -255,7 +370,7 @@ pub fn list_plugins() -> Vec<ProviderInfo> {
             .unwrap_or_default()
             .into_iter()
             .filter_map(|r| r.ok())
-            .map(|ParsedPlugin { path: _, plugin: p }| PluginInfo {
+            .map(|ParsedPlugin { plugin: p, .. }| PluginInfo {
                 name: p.name,
                 hooks_count: p.hooks.len(),
                 skill_groups_count: p.skills.len(),
@@ -301,23 +416,30 @@ pub fn find_plugin(name: &str) -> Option<ParsedPlugin> {
 /// For `git` sources: computes the cache path under `~/.symposium/cache/plugin-sources/`.
 ///
 /// Does no network I/O — just computes paths.
-fn resolve_plugin_source_dirs() -> Vec<PathBuf> {
+fn resolve_plugin_source_dirs() -> Vec<SourceDir> {
     let sources = crate::config::plugin_sources();
     let config_dir = crate::config::config_dir();
     let cache_base = crate::config::cache_dir().join("plugin-sources");
 
     let mut dirs = Vec::new();
     for source in &sources {
+        let mut push = |dir: PathBuf| {
+            dirs.push(SourceDir {
+                name: source.name.clone(),
+                dir,
+                precedence: source.precedence,
+            })
+        };
         if let Some(path) = &source.path {
             let p = PathBuf::from(path);
             if p.is_absolute() {
-                dirs.push(p);
+                push(p);
             } else {
-                dirs.push(config_dir.join(p));
+                push(config_dir.join(p));
             }
         } else if let Some(git_url) = &source.git {
             match crate::git_source::parse_github_url(git_url) {
-                Ok(gh) => dirs.push(cache_base.join(gh.cache_key())),
+                Ok(gh) => push(cache_base.join(gh.cache_key())),
                 Err(e) => {
                     tracing::warn!(source = %source.name, error = %e, "bad plugin source URL");
                 }
@@ -368,18 +490,27 @@ pub fn load_registry() -> PluginRegistry {
     let mut plugins = Vec::new();
     let mut standalone_skills = Vec::new();
 
-    for dir in resolve_plugin_source_dirs() {
+    for SourceDir {
+        name,
+        dir,
+        precedence,
+    } in resolve_plugin_source_dirs()
+    {
         match scan_source_dir(&dir) {
             Ok(contents) => {
                 for result in contents.plugins {
                     match result {
-                        Ok(p) => plugins.push(p),
+                        Ok(p) => plugins.push(ParsedPlugin { precedence, ..p }),
                         Err(e) => tracing::warn!(error = %e, "failed to load plugin"),
                     }
                 }
                 for skill_md in contents.skill_files {
-                    match crate::skills::load_standalone_skill(&skill_md) {
-                        Ok(skill) => standalone_skills.push(skill),
+                    match crate::skills::load_standalone_skill(&skill_md, &dir) {
+                        Ok(skill) => standalone_skills.push(StandaloneSkill {
+                            source: name.clone(),
+                            precedence,
+                            skill,
+                        }),
                         Err(e) => tracing::warn!(
                             path = %skill_md.display(),
                             error = %e,
@@ -400,6 +531,44 @@ pub fn load_registry() -> PluginRegistry {
     }
 }
 
+/// Fingerprint the contents of all configured plugin source directories.
+///
+/// Hashes the path, size, and modification time of every non-hidden entry,
+/// so adding, removing, or editing a manifest or skill file changes the
+/// result. Long-running processes use this to decide when a registry
+/// returned by [`load_registry`] is stale.
+pub fn source_fingerprint() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for SourceDir { dir, .. } in resolve_plugin_source_dirs() {
+        dir.hash(&mut hasher);
+        hash_dir_tree(&dir, &mut hasher);
+    }
+    hasher.finish()
+}
+
+fn hash_dir_tree(dir: &Path, hasher: &mut DefaultHasher) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        path.hash(hasher);
+        meta.len().hash(hasher);
+        meta.modified().ok().hash(hasher);
+        if meta.is_dir() {
+            hash_dir_tree(&path, hasher);
+        }
+    }
+}
+
 /// Scan a plugin source directory for TOML plugin manifests and standalone skills.
 ///
 /// Plugins are `.toml` files at the top level. Standalone skills are discovered
@@ -509,7 +678,7 @@ pub fn validate_source_dir(dir: &Path) -> Result<Vec<ValidationResult>> {
     }
 
     for skill_md in contents.skill_files {
-        let result = crate::skills::load_standalone_skill(&skill_md).map(|_| ());
+        let result = crate::skills::load_standalone_skill(&skill_md, dir).map(|_| ());
         results.push(ValidationResult {
             path: skill_md,
             kind: ValidationKind::Skill,
@@ -520,6 +689,15 @@ pub fn validate_source_dir(dir: &Path) -> Result<Vec<ValidationResult>> {
     Ok(results)
 }
 
+/// Validate a single standalone SKILL.md as [`validate_source_dir`] would,
+/// taking the directory that holds the skill's directory as the source
+/// directory its includes must stay in.
+pub fn validate_skill_file(skill_md: &Path) -> Result<()> {
+    let skill_dir = skill_md.parent().unwrap_or(Path::new("."));
+    let root = skill_dir.parent().unwrap_or(skill_dir);
+    crate::skills::load_standalone_skill(skill_md, root).map(|_| ())
+}
+
 /// Collect all crate names referenced in predicates across a plugin source directory.
 ///
 /// Scans TOML plugin manifests (skill group `crates`/`applies-when`) and
@@ -545,7 +723,7 @@ pub fn collect_crate_names_in_source_dir(dir: &Path) -> Result<Vec<String>> {
     }
 
     for skill_md in contents.skill_files {
-        if let Ok(skill) = crate::skills::load_standalone_skill(&skill_md) {
+        if let Ok(skill) = crate::skills::load_standalone_skill(&skill_md, dir) {
             for pred in &skill.crates {
                 pred.collect_crate_names(&mut names);
             }
@@ -577,6 +755,8 @@ pub async fn check_crate_exists(crate_name: &str) -> bool {
 pub fn load_plugin(manifest_path: &Path) -> Result<ParsedPlugin> {
     let content = fs::read_to_string(manifest_path)?;
     let manifest: PluginManifest = toml::from_str(&content)?;
+    validate_commands(&manifest.commands)?;
+    validate_mcp_servers(&manifest.mcp_servers)?;
 
     Ok(ParsedPlugin {
         path: manifest_path.to_path_buf(),
@@ -585,10 +765,46 @@ pub fn load_plugin(manifest_path: &Path) -> Result<ParsedPlugin> {
             installation: manifest.installation,
             hooks: manifest.hooks,
             skills: manifest.skills,
+            commands: manifest.commands,
+            mcp_servers: manifest.mcp_servers,
+            related: manifest.related,
         },
+        precedence: Precedence::default(),
     })
 }
 
+/// Check that plugin `rust` subcommands have usable names.
+fn validate_commands(commands: &[RustCommand]) -> Result<()> {
+    for command in commands {
+        let name = &command.name;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!("command name {name:?} must be a single non-empty word");
+        }
+        if crate::rust_command::is_builtin(name) {
+            bail!("command `{name}` conflicts with a built-in `rust` command");
+        }
+    }
+    Ok(())
+}
+
+/// Check that plugin MCP server names can be used as tool name prefixes.
+fn validate_mcp_servers(servers: &[PluginMcpServer]) -> Result<()> {
+    for server in servers {
+        let name = &server.name;
+        if name.is_empty()
+            || name.contains("__")
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "MCP server name {name:?} must be non-empty and use only letters, digits, `-` and `_` (no `__`)"
+            );
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -601,6 +817,9 @@ mod tests {
             installation: manifest.installation,
             hooks: manifest.hooks,
             skills: manifest.skills,
+            commands: manifest.commands,
+            mcp_servers: manifest.mcp_servers,
+            related: manifest.related,
         })
     }
 
@@ -813,6 +1032,40 @@ mod tests {
         assert_eq!(err_count, 2);
     }
 
+    #[test]
+    fn validate_source_dir_reports_missing_include() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        let skill_dir = dir.join("my-skill");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: my-skill
+                crates: serde
+                ---
+
+                <!-- include: ../fragments/errors.md -->
+            "},
+        )
+        .unwrap();
+
+        let results = validate_source_dir(dir).unwrap();
+        assert_eq!(results.len(), 1);
+        let err = results[0].result.as_ref().unwrap_err();
+        assert!(format!("{err:#}").contains("include `../fragments/errors.md`"));
+        assert!(format!("{err:#}").contains("not found"));
+
+        let err = validate_skill_file(&skill_dir.join("SKILL.md")).unwrap_err();
+        assert!(format!("{err:#}").contains("include `../fragments/errors.md`"));
+
+        std::fs::create_dir_all(dir.join("fragments")).unwrap();
+        std::fs::write(dir.join("fragments/errors.md"), "Use anyhow.\n").unwrap();
+        validate_skill_file(&skill_dir.join("SKILL.md")).unwrap();
+    }
+
     #[test]
     fn collect_crate_names_from_source_dir() {
         let tmp = tempfile::tempdir().unwrap();
@@ -925,4 +1178,95 @@ mod tests {
         assert!(plugin.skills[0].crates.as_ref().unwrap()[0].references_crate("serde"));
         assert!(plugin.skills[1].crates.as_ref().unwrap()[0].references_crate("tokio"));
     }
+
+    #[test]
+    fn parse_manifest_with_commands() {
+        let toml = indoc! {r#"
+            name = "audit-plugin"
+
+            [[commands]]
+            name = "audit"
+            description = "Check dependencies for advisories"
+            command = "cargo audit"
+            applies-when = ["tokio"]
+        "#};
+        let plugin = from_str(toml).expect("parse");
+        assert_eq!(plugin.commands.len(), 1);
+        let command = &plugin.commands[0];
+        assert_eq!(command.name, "audit");
+        assert_eq!(command.command, "cargo audit");
+
+        let tokio = ("tokio".to_string(), semver::Version::new(1, 0, 0));
+        assert!(command.applies_to(&[tokio]));
+        assert!(!command.applies_to(&[]));
+    }
+
+    #[test]
+    fn load_plugin_rejects_builtin_command_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("plugin.toml");
+        std::fs::write(
+            &path,
+            indoc! {r#"
+                name = "shadowing"
+
+                [[commands]]
+                name = "help"
+                command = "echo nope"
+            "#},
+        )
+        .unwrap();
+
+        let err = load_plugin(&path).unwrap_err();
+        assert!(err.to_string().contains("conflicts with a built-in"));
+    }
+
+    #[test]
+    fn related_crates_merge_across_plugins() {
+        let parse = |toml: &str| ParsedPlugin {
+            path: PathBuf::from("/plugins/p.toml"),
+            plugin: from_str(toml).expect("parse"),
+            precedence: Precedence::default(),
+        };
+        let registry = PluginRegistry {
+            plugins: vec![
+                parse(indoc! {r#"
+                    name = "serde-plugin"
+                    related = { serde_json = ["serde"], serde_yaml = ["serde"] }
+                "#}),
+                parse(indoc! {r#"
+                    name = "other"
+
+                    [related]
+                    serde_json = ["serde", "serde_json", "itoa"]
+                "#}),
+            ],
+            standalone_skills: Vec::new(),
+        };
+        assert_eq!(registry.related_crates("serde_json"), ["serde", "itoa"]);
+        assert!(registry.related_crates("serde").is_empty());
+    }
+
+    #[test]
+    fn parse_manifest_with_mcp_servers() {
+        let toml = indoc! {r#"
+            name = "sqlx-plugin"
+
+            [[mcp-servers]]
+            name = "sqlx"
+            command = "sqlx-mcp"
+            args = ["--offline"]
+            applies-when = ["sqlx>=0.7"]
+        "#};
+        let plugin = from_str(toml).expect("parse");
+        assert_eq!(plugin.mcp_servers.len(), 1);
+        let server = &plugin.mcp_servers[0];
+        assert_eq!(server.name, "sqlx");
+        assert_eq!(server.command, "sqlx-mcp");
+        assert_eq!(server.args, ["--offline"]);
+
+        let sqlx = ("sqlx".to_string(), semver::Version::new(0, 8, 0));
+        assert!(server.applies_to(&[sqlx]));
+        assert!(!server.applies_to(&[]));
+    }
 }
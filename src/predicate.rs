@@ -39,6 +39,30 @@ impl Predicate {
     pub fn collect_crate_names(&self, out: &mut std::collections::BTreeSet<String>) {
         out.insert(self.name.clone());
     }
+
+    /// How narrowly this predicate constrains the crate's version.
+    ///
+    /// A bare crate name scores 0. Each comparator scores by operator
+    /// (exact 4, tilde 3, caret 2, anything else 1), plus one for each of
+    /// the minor and patch components it spells out, so `serde==1.0.193`
+    /// outranks `serde=1.0`, which outranks `serde>=1.0`.
+    pub fn specificity(&self) -> u32 {
+        let Some(req) = &self.version_req else {
+            return 0;
+        };
+        req.comparators
+            .iter()
+            .map(|c| {
+                let op = match c.op {
+                    semver::Op::Exact => 4,
+                    semver::Op::Tilde => 3,
+                    semver::Op::Caret => 2,
+                    _ => 1,
+                };
+                op + u32::from(c.minor.is_some()) + u32::from(c.patch.is_some())
+            })
+            .sum()
+    }
 }
 
 /// Parse a list of predicate strings.
@@ -441,6 +465,18 @@ mod tests {
         assert_eq!(preds.len(), 2);
     }
 
+    // --- Specificity ---
+
+    #[test]
+    fn specificity_orders_constraints() {
+        let score = |s: &str| parse(s).unwrap().specificity();
+        assert_eq!(score("tokio"), 0);
+        assert!(score("tokio>=1.0") > score("tokio"));
+        assert!(score("tokio=1.40") > score("tokio>=1.0"));
+        assert!(score("tokio>=1.0,<1.40") > score("tokio>=1.0"));
+        assert!(score("tokio==1.40.0") > score("tokio=1.40"));
+    }
+
     // --- Error tests ---
 
     #[test]
@@ -0,0 +1,55 @@
+//! Progress reporting for long-running operations.
+//!
+//! Code deep in the call stack (crate downloads, git source refreshes) calls
+//! [`report`] at each stage. Whoever starts the operation decides where the
+//! reports go by running it inside [`with_reporter`]; the MCP server forwards
+//! them to the client as progress notifications. Outside such a scope, reports
+//! are only logged.
+
+use std::future::Future;
+use std::sync::Arc;
+
+/// Receives progress messages.
+pub type Reporter = Arc<dyn Fn(&str) + Send + Sync>;
+
+tokio::task_local! {
+    static REPORTER: Reporter;
+}
+
+/// Run `future`, sending any progress it reports to `reporter`.
+pub async fn with_reporter<F: Future>(reporter: Reporter, future: F) -> F::Output {
+    REPORTER.scope(reporter, future).await
+}
+
+/// Report that an operation has reached a new stage.
+pub fn report(message: impl AsRef<str>) {
+    let message = message.as_ref();
+    tracing::debug!(%message, "progress");
+    let _ = REPORTER.try_with(|reporter| reporter(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn reports_reach_scoped_reporter() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let reporter: Reporter = {
+            let seen = seen.clone();
+            Arc::new(move |m: &str| seen.lock().unwrap().push(m.to_string()))
+        };
+
+        report("before");
+        with_reporter(reporter, async {
+            report("one");
+            tokio::task::yield_now().await;
+            report("two");
+        })
+        .await;
+        report("after");
+
+        assert_eq!(*seen.lock().unwrap(), vec!["one", "two"]);
+    }
+}
@@ -0,0 +1,469 @@
+//! The `rust` command dispatcher.
+//!
+//! `symposium rust <command>` and the MCP `rust` tool share this entry point.
+//! Built-in commands are handled here; any other command is looked up among
+//! the `[[commands]]` that plugins register, restricted to those whose
+//! `applies-when` matches the current workspace.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::diagnostics;
+use crate::plugins::{ParsedPlugin, PluginRegistry, RustCommand};
+use crate::skills;
+
+/// Built-in commands: name, usage, description.
+const BUILTINS: &[(&str, &str, &str)] = &[
+    ("help", "help", "Show this message"),
+    (
+        "skills",
+        "skills",
+        "List skills available for the workspace's dependencies",
+    ),
+    (
+        "guidance",
+        "guidance [topic]",
+        "Show general Rust guidance, optionally on one topic",
+    ),
+    (
+        "crate",
+        "crate <name> [version]",
+        "Show a crate's source location and guidance",
+    ),
+    (
+        "diagnose",
+        "diagnose [--from <file>] [cargo check args...]",
+        "Run `cargo check` (or read its saved JSON output) and show guidance for the crates involved",
+    ),
+    (
+        "explain",
+        "explain <code-or-lint>",
+        "Explain a compiler error code or lint (e.g. E0502, clippy::needless_return)",
+    ),
+];
+
+/// Whether `name` is a built-in command (plugins may not register these).
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.iter().any(|(builtin, _, _)| *builtin == name)
+}
+
+/// Split a command string (as passed to the MCP tool) into words.
+pub fn split_command(command: &str) -> Vec<String> {
+    command.split_whitespace().map(str::to_string).collect()
+}
+
+/// Run a `rust` command. An empty command is treated as `help`.
+///
+/// `root` is the workspace directory plugin commands run in; `workspace`
+/// holds its dependencies.
+pub async fn execute(
+    args: &[String],
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let Some((command, args)) = args.split_first() else {
+        return Ok(help_output(registry, workspace));
+    };
+
+    match (command.as_str(), args) {
+        ("help", _) => Ok(help_output(registry, workspace)),
+        ("skills", []) => Ok(skills::list_output(registry, workspace).await),
+        ("guidance", []) => Ok(skills::rust_guidance_output(None, registry, root, workspace).await),
+        ("guidance", [topic]) => {
+            Ok(skills::rust_guidance_output(Some(topic), registry, root, workspace).await)
+        }
+        ("crate", [name]) => {
+            skills::info_output(name, None, Default::default(), registry, root, workspace).await
+        }
+        ("crate", [name, version]) => {
+            skills::info_output(
+                name,
+                Some(version),
+                Default::default(),
+                registry,
+                root,
+                workspace,
+            )
+            .await
+        }
+        ("explain", [diagnostic]) => explain(diagnostic, registry, root, workspace).await,
+        ("diagnose", [flag, file]) if flag == "--from" => {
+            let path = workspace_file(root, file)?;
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            Ok(diagnostics::diagnose_output(&json, registry, root, workspace).await)
+        }
+        ("diagnose", args) => {
+            let json = diagnostics::run_cargo_check(root, args).await?;
+            Ok(diagnostics::diagnose_output(&json, registry, root, workspace).await)
+        }
+        (name, _) if is_builtin(name) => {
+            let usage = BUILTINS
+                .iter()
+                .find(|(builtin, _, _)| *builtin == name)
+                .map_or(name, |(_, usage, _)| usage);
+            bail!("usage: {usage}")
+        }
+        (name, args) => {
+            let Some((plugin, command)) = find_plugin_command(registry, name, workspace) else {
+                bail!("Unknown command: {name}. Use `help` to see available commands.");
+            };
+            run_plugin_command(plugin, command, args, root).await
+        }
+    }
+}
+
+/// Resolve `file`, relative to the workspace `root`, to a path that stays
+/// inside the workspace, so callers cannot read arbitrary files.
+fn workspace_file(root: &Path, file: &str) -> Result<std::path::PathBuf> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", root.display()))?;
+    let path = root.join(file);
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    if !path.starts_with(&root) {
+        bail!("`{file}` is outside the workspace {}", root.display());
+    }
+    Ok(path)
+}
+
+/// The tutorial followed by every command available in this workspace.
+fn help_output(registry: &PluginRegistry, workspace: &[(String, semver::Version)]) -> String {
+    let mut out = crate::tutorial::render_mcp();
+    out.push_str("\n## Commands\n\n");
+    for (_, usage, description) in BUILTINS {
+        out.push_str(&format!("- `{usage}`: {description}\n"));
+    }
+    for (plugin, command) in available_plugin_commands(registry, workspace) {
+        let description = command.description.as_deref().unwrap_or("(no description)");
+        out.push_str(&format!(
+            "- `{}`: {description} (from plugin `{}`)\n",
+            command.name, plugin.plugin.name
+        ));
+    }
+    out
+}
+
+/// Plugin commands whose `applies-when` matches the workspace, in registry order.
+fn available_plugin_commands<'a>(
+    registry: &'a PluginRegistry,
+    workspace: &[(String, semver::Version)],
+) -> Vec<(&'a ParsedPlugin, &'a RustCommand)> {
+    registry
+        .plugins
+        .iter()
+        .flat_map(|plugin| plugin.plugin.commands.iter().map(move |c| (plugin, c)))
+        .filter(|(_, command)| command.applies_to(workspace))
+        .collect()
+}
+
+/// Find the first available plugin command called `name`.
+fn find_plugin_command<'a>(
+    registry: &'a PluginRegistry,
+    name: &str,
+    workspace: &[(String, semver::Version)],
+) -> Option<(&'a ParsedPlugin, &'a RustCommand)> {
+    available_plugin_commands(registry, workspace)
+        .into_iter()
+        .find(|(_, command)| command.name == name)
+}
+
+/// Run a plugin command in the workspace root and return its standard output.
+///
+/// The remaining arguments are passed as positional parameters, and
+/// `SYMPOSIUM_PLUGIN_DIR` points at the directory containing the manifest so
+/// commands can refer to scripts shipped with the plugin.
+async fn run_plugin_command(
+    plugin: &ParsedPlugin,
+    command: &RustCommand,
+    args: &[String],
+    root: &Path,
+) -> Result<String> {
+    tracing::info!(plugin = %plugin.plugin.name, command = %command.name, ?args, "running plugin command");
+
+    let plugin_dir = plugin.path.parent().unwrap_or(Path::new("."));
+    let output = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", command.command))
+        .arg(&command.name)
+        .args(args)
+        .current_dir(root)
+        .env("SYMPOSIUM_PLUGIN_DIR", plugin_dir)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("failed to run command `{}`", command.name))?;
+
+    if !output.status.success() {
+        bail!(
+            "command `{}` failed ({}):\n{}",
+            command.name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Explain an error code or lint, followed by any plugin skills tagged for it.
+///
+/// Explanations come from the local toolchain: `rustc --explain` for error
+/// codes, `rustc -W help` for rustc lints, and `cargo clippy --explain` for
+/// clippy lints.
+async fn explain(
+    diagnostic: &str,
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let explanation = toolchain_explanation(diagnostic).await;
+    let guidance = skills::diagnostic_guidance_output(diagnostic, registry, root, workspace).await;
+
+    match explanation {
+        Ok(mut out) => {
+            out.push_str(&guidance);
+            Ok(out)
+        }
+        Err(e) if !guidance.is_empty() => Ok(format!("{e}\n{guidance}")),
+        Err(e) => Err(e),
+    }
+}
+
+async fn toolchain_explanation(diagnostic: &str) -> Result<String> {
+    let name = skills::normalize_diagnostic(diagnostic);
+
+    if skills::is_error_code(&name) {
+        return match run_toolchain("rustc", &["--explain", &name]).await? {
+            Some(text) => Ok(text),
+            None => bail!("rustc has no explanation for {name}"),
+        };
+    }
+
+    let clippy_only = diagnostic.trim().to_lowercase().starts_with("clippy::");
+    if !clippy_only
+        && let Some(help) = run_toolchain("rustc", &["-W", "help"]).await?
+        && let Some((level, meaning)) = find_rustc_lint(&help, &name)
+    {
+        return Ok(format!(
+            "`{name}` is a rustc lint (default level: {level}).\n\n{meaning}\n"
+        ));
+    }
+
+    match run_toolchain("cargo", &["clippy", "--explain", &name]).await {
+        Ok(Some(text)) if !text.starts_with("unknown lint") => {
+            Ok(format!("`clippy::{name}` is a clippy lint.\n\n{text}"))
+        }
+        Ok(_) => bail!("`{diagnostic}` is not a known error code, rustc lint, or clippy lint"),
+        Err(e) => bail!("`{diagnostic}` is not a rustc lint, and clippy is unavailable: {e}"),
+    }
+}
+
+/// Run a toolchain command, returning its output if it succeeded.
+async fn run_toolchain(program: &str, args: &[&str]) -> Result<Option<String>> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .with_context(|| format!("failed to run `{program}`"))?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// Look up a lint in the table printed by `rustc -W help`, returning its
+/// default level and description.
+fn find_rustc_lint<'a>(help: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+    let wanted = name.replace('_', "-");
+    help.lines()
+        .take_while(|line| !line.starts_with("Lint groups"))
+        .find_map(|line| {
+            let line = line.trim_start();
+            let (lint, rest) = line.split_once(char::is_whitespace)?;
+            if lint != wanted {
+                return None;
+            }
+            let (level, meaning) = rest.trim_start().split_once(char::is_whitespace)?;
+            Some((level, meaning.trim()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::Plugin;
+
+    fn registry_with(commands: Vec<RustCommand>) -> PluginRegistry {
+        PluginRegistry {
+            plugins: vec![ParsedPlugin {
+                path: "/plugins/example.toml".into(),
+                plugin: Plugin {
+                    name: "example".to_string(),
+                    installation: None,
+                    hooks: Vec::new(),
+                    skills: Vec::new(),
+                    commands,
+                    mcp_servers: Vec::new(),
+                    related: Default::default(),
+                },
+                precedence: Default::default(),
+            }],
+            standalone_skills: Vec::new(),
+        }
+    }
+
+    fn command(name: &str, shell: &str, applies_when: Option<&str>) -> RustCommand {
+        RustCommand {
+            name: name.to_string(),
+            description: Some(format!("The {name} command")),
+            command: shell.to_string(),
+            applies_when: applies_when
+                .map(|p| crate::predicate::parse_predicates(&[p.to_string()]).unwrap()),
+        }
+    }
+
+    fn args(command: &str) -> Vec<String> {
+        split_command(command)
+    }
+
+    #[tokio::test]
+    async fn help_lists_builtins_and_applicable_plugin_commands() {
+        let registry = registry_with(vec![
+            command("audit", "true", None),
+            command("tokio-lint", "true", Some("tokio")),
+        ]);
+        let root = tempfile::tempdir().unwrap();
+
+        let output = execute(&args("help"), &registry, root.path(), &[])
+            .await
+            .unwrap();
+        assert!(output.contains("- `crate <name> [version]`"));
+        assert!(output.contains("- `explain <code-or-lint>`"));
+        assert!(output.contains("- `audit`: The audit command (from plugin `example`)"));
+        assert!(!output.contains("tokio-lint"));
+
+        let workspace = [("tokio".to_string(), semver::Version::new(1, 0, 0))];
+        let output = execute(&[], &registry, root.path(), &workspace)
+            .await
+            .unwrap();
+        assert!(output.contains("- `tokio-lint`"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn plugin_command_runs_in_workspace_with_args() {
+        let registry = registry_with(vec![command("where", "pwd; echo", None)]);
+        let root = tempfile::tempdir().unwrap();
+
+        let output = execute(&args("where one two"), &registry, root.path(), &[])
+            .await
+            .unwrap();
+        let root = root.path().canonicalize().unwrap();
+        assert_eq!(output, format!("{}\none two\n", root.display()));
+    }
+
+    #[tokio::test]
+    async fn unknown_and_unavailable_commands_fail() {
+        let registry = registry_with(vec![command("tokio-lint", "true", Some("tokio"))]);
+        let root = tempfile::tempdir().unwrap();
+
+        for command in ["frobnicate", "tokio-lint"] {
+            let err = execute(&args(command), &registry, root.path(), &[])
+                .await
+                .unwrap_err();
+            assert!(err.to_string().starts_with("Unknown command"), "{err}");
+        }
+
+        let err = execute(&args("crate"), &registry, root.path(), &[])
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "usage: crate <name> [version]");
+    }
+
+    #[tokio::test]
+    async fn diagnose_from_rejects_files_outside_workspace() {
+        let registry = registry_with(Vec::new());
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("workspace");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(tmp.path().join("x"), "").unwrap();
+        std::fs::write(root.join("check.json"), "").unwrap();
+
+        for file in ["/etc/passwd", "../x"] {
+            let err = execute(
+                &args(&format!("diagnose --from {file}")),
+                &registry,
+                &root,
+                &[],
+            )
+            .await
+            .unwrap_err();
+            assert!(err.to_string().contains("outside the workspace"), "{err}");
+        }
+        execute(&args("diagnose --from check.json"), &registry, &root, &[])
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn finds_rustc_lints_in_help_table() {
+        let help = indoc::indoc! {"
+            Lint checks provided by rustc:
+
+                                name  default  meaning
+                                ----  -------  -------
+                    unused-variables  warn     detect variables which are not used in any way
+                       unsafe-code    allow    usage of `unsafe` code and outer attributes
+
+            Lint groups provided by rustc:
+
+                              unused  unused-imports, unused-variables
+        "};
+        assert_eq!(
+            find_rustc_lint(help, "unused_variables"),
+            Some(("warn", "detect variables which are not used in any way"))
+        );
+        assert_eq!(find_rustc_lint(help, "unused"), None);
+        assert_eq!(find_rustc_lint(help, "needless_return"), None);
+    }
+
+    #[tokio::test]
+    async fn explain_includes_tagged_skills() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_md = tmp.path().join("SKILL.md");
+        std::fs::write(
+            &skill_md,
+            indoc::indoc! {"
+                ---
+                name: borrowck-tips
+                scope: rust
+                diagnostics: E0502, clippy::needless-return
+                activation: default
+                ---
+
+                Split the borrow.
+            "},
+        )
+        .unwrap();
+        let mut registry = registry_with(Vec::new());
+        registry.standalone_skills = vec![crate::plugins::StandaloneSkill {
+            source: "test".to_string(),
+            precedence: Default::default(),
+            skill: skills::load_standalone_skill(&skill_md, tmp.path()).unwrap(),
+        }];
+
+        let output = execute(&args("explain e0502"), &registry, tmp.path(), &[])
+            .await
+            .unwrap();
+        assert!(output.contains("borrowed again with a different mutability"));
+        assert!(output.contains("Split the borrow."));
+
+        let guidance =
+            skills::diagnostic_guidance_output("needless_return", &registry, tmp.path(), &[]).await;
+        assert!(guidance.contains("Split the borrow."));
+    }
+}
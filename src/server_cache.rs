@@ -0,0 +1,257 @@
+//! Caches for long-running processes such as `symposium mcp`.
+//!
+//! Loading the plugin registry parses every manifest and skill file, and
+//! listing workspace dependencies or details runs `cargo metadata`. These are
+//! cached here and revalidated with cheap mtime checks:
+//!
+//! * the registry is reloaded when `config.toml` changes, or when anything
+//!   under a plugin source directory changes (checked at most once every
+//!   [`SOURCES_CHECK_INTERVAL`], since that walks every source directory);
+//! * a workspace's dependency list and details are recomputed when its
+//!   `Cargo.lock` or `Cargo.toml` changes.
+//!
+//! Each reload of the registry is announced to
+//! [`registry_changes`](ServerCache::registry_changes), so that the MCP
+//! transports can tell their clients that the skills changed.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::crate_sources::{self, WorkspaceDetails};
+use crate::plugins::{self, PluginRegistry};
+
+/// Dependency list as returned by [`crate_sources::workspace_semver_pairs`].
+pub type WorkspaceDeps = Vec<(String, semver::Version)>;
+
+/// Minimum time between fingerprints of the plugin source directories.
+pub const SOURCES_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub struct ServerCache {
+    registry: Mutex<Option<CachedRegistry>>,
+    /// Number of times the registry was reloaded after its first load.
+    reloads: tokio::sync::watch::Sender<u64>,
+    workspaces: PerRoot<WorkspaceDeps>,
+    details: PerRoot<WorkspaceDetails>,
+}
+
+/// One slot per workspace root, each locked separately so that loading one
+/// workspace does not hold up calls for another.
+type PerRoot<T> = Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<Option<CachedWorkspace<T>>>>>>;
+
+struct CachedRegistry {
+    config_mtime: Option<SystemTime>,
+    sources_fingerprint: u64,
+    sources_checked: Instant,
+    registry: Arc<PluginRegistry>,
+}
+
+struct CachedWorkspace<T> {
+    stamp: Vec<Option<SystemTime>>,
+    value: Arc<T>,
+}
+
+impl ServerCache {
+    /// The cache shared by the whole process.
+    pub fn shared() -> Arc<ServerCache> {
+        static SHARED: LazyLock<Arc<ServerCache>> = LazyLock::new(Default::default);
+        SHARED.clone()
+    }
+
+    /// Return the plugin registry, reloading it if its inputs changed.
+    pub fn registry(&self) -> Arc<PluginRegistry> {
+        let mut cached = self.registry.lock().unwrap();
+
+        let config_mtime = mtime(&crate::config::config_path());
+        if let Some(c) = cached.as_mut()
+            && c.config_mtime == config_mtime
+        {
+            if c.sources_checked.elapsed() < SOURCES_CHECK_INTERVAL {
+                return c.registry.clone();
+            }
+            c.sources_checked = Instant::now();
+            if c.sources_fingerprint == plugins::source_fingerprint() {
+                return c.registry.clone();
+            }
+        } else if cached.is_some() {
+            // Plugin source locations come from the config, so reload it
+            // before fingerprinting the sources.
+            crate::config::reload();
+        }
+
+        let reload = cached.is_some();
+        tracing::debug!(reload, "loading plugin registry");
+        let sources_fingerprint = plugins::source_fingerprint();
+        let registry = Arc::new(plugins::load_registry());
+        *cached = Some(CachedRegistry {
+            config_mtime,
+            sources_fingerprint,
+            sources_checked: Instant::now(),
+            registry: registry.clone(),
+        });
+        if reload {
+            self.reloads.send_modify(|reloads| *reloads += 1);
+        }
+        registry
+    }
+
+    /// Receiver that is notified each time [`registry`](Self::registry)
+    /// reloads a previously loaded registry.
+    pub fn registry_changes(&self) -> tokio::sync::watch::Receiver<u64> {
+        self.reloads.subscribe()
+    }
+
+    /// Return the dependencies of the workspace at `root`, recomputing them
+    /// if its `Cargo.lock` or `Cargo.toml` changed.
+    pub async fn workspace_deps(&self, root: &Path) -> Arc<WorkspaceDeps> {
+        load_per_root(
+            &self.workspaces,
+            root,
+            "dependencies",
+            crate_sources::workspace_semver_pairs,
+        )
+        .await
+    }
+
+    /// Return the edition and enabled features of the workspace at `root`,
+    /// for templated skills, recomputing them as [`workspace_deps`] does.
+    ///
+    /// [`workspace_deps`]: Self::workspace_deps
+    pub async fn workspace_details(&self, root: &Path) -> Arc<WorkspaceDetails> {
+        load_per_root(
+            &self.details,
+            root,
+            "details",
+            crate_sources::workspace_details,
+        )
+        .await
+    }
+}
+
+/// Return the `what` cached for `root` in `slots`, loading it with `load`
+/// (off the async runtime, since it runs `cargo metadata`) if the workspace
+/// changed.
+async fn load_per_root<T: Default + Send + Sync + 'static>(
+    slots: &PerRoot<T>,
+    root: &Path,
+    what: &str,
+    load: fn(&Path) -> T,
+) -> Arc<T> {
+    let slot = slots
+        .lock()
+        .unwrap()
+        .entry(root.to_path_buf())
+        .or_default()
+        .clone();
+    let mut slot = slot.lock().await;
+
+    let stamp = workspace_stamp(root);
+    if let Some(cached) = slot.as_ref()
+        && cached.stamp == stamp
+    {
+        return cached.value.clone();
+    }
+
+    tracing::debug!(root = %root.display(), %what, "loading workspace");
+    let dir = root.to_path_buf();
+    let (value, stamp) = tokio::task::spawn_blocking(move || {
+        let value = load(&dir);
+        // `cargo metadata` may have written `Cargo.lock`; stamp afterwards
+        // so that write does not look like a change on the next call.
+        (value, workspace_stamp(&dir))
+    })
+    .await
+    .unwrap_or_else(|e| {
+        tracing::warn!(error = %e, %what, "loading workspace failed");
+        (T::default(), Vec::new())
+    });
+    let value = Arc::new(value);
+    *slot = Some(CachedWorkspace {
+        stamp,
+        value: value.clone(),
+    });
+    value
+}
+
+/// Modification times of the files that determine a workspace's dependencies.
+pub fn workspace_stamp(root: &Path) -> Vec<Option<SystemTime>> {
+    let lock = root
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|p| p.is_file());
+    vec![
+        mtime(&root.join("Cargo.toml")),
+        lock.and_then(|p| mtime(&p)),
+    ]
+}
+
+/// A stable hash of the canonical `root`, for naming files about the
+/// workspace under the cache directory.
+///
+/// It uses 64-bit FNV-1a rather than the std hasher, whose output may change
+/// between Rust versions.
+pub fn workspace_hash(root: &Path) -> u64 {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    root.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Modification time of `path`, if it exists.
+pub fn mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+
+    fn set_mtime(path: &Path, offset_secs: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(offset_secs))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn registry_reload_is_announced() {
+        let cache = ServerCache::default();
+        let mut changes = cache.registry_changes();
+        let registry = cache.registry();
+        assert!(!changes.has_changed().unwrap());
+
+        // Pretend the sources were fingerprinted long ago, and differently.
+        if let Some(cached) = cache.registry.lock().unwrap().as_mut() {
+            cached.sources_fingerprint = cached.sources_fingerprint.wrapping_add(1);
+            cached.sources_checked = Instant::now() - SOURCES_CHECK_INTERVAL;
+        }
+        assert!(!Arc::ptr_eq(&registry, &cache.registry()));
+        assert!(changes.has_changed().unwrap());
+        changes.mark_unchanged();
+
+        cache.registry();
+        assert!(!changes.has_changed().unwrap());
+    }
+
+    #[test]
+    fn workspace_stamp_tracks_lockfile_in_ancestor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let member = tmp.path().join("member");
+        fs::create_dir_all(&member).unwrap();
+        fs::write(member.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(tmp.path().join("Cargo.lock"), "").unwrap();
+        set_mtime(&tmp.path().join("Cargo.lock"), 1_000);
+
+        let before = workspace_stamp(&member);
+        assert!(before.iter().all(Option::is_some));
+
+        set_mtime(&tmp.path().join("Cargo.lock"), 2_000);
+        assert_ne!(before, workspace_stamp(&member));
+    }
+}
@@ -0,0 +1,425 @@
+//! Export skills to an agent's native skills directory
+//! (`symposium skills export`).
+//!
+//! Each skill applicable to the workspace is written as
+//! `<skills dir>/<name>/SKILL.md`, next to copies of its resource files.
+//! What was written is recorded in a manifest in the skills directory, so a
+//! later export can update those skills and remove the ones that no longer
+//! apply. A skill directory that is not in the manifest was written by hand
+//! and is left alone. The manifest records which workspaces exported each
+//! skill, so in the user's skills directory one workspace's export does not
+//! remove another's skills.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::plugins::PluginRegistry;
+use crate::skills::{self, SkillWithGroupContext};
+
+/// Name of the manifest file in the skills directory.
+const MANIFEST_FILE: &str = ".symposium-export.json";
+
+/// Agent whose skills directory to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportTarget {
+    /// Claude Code (`.claude/skills`).
+    Claude,
+}
+
+/// Where to write the skills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    /// The workspace's skills directory (e.g. `.claude/skills`).
+    Project,
+    /// The user's skills directory (e.g. `~/.claude/skills`).
+    User,
+}
+
+impl ExportTarget {
+    /// The skills directory for `scope`, with `root` as the workspace.
+    fn skills_dir(self, scope: ExportScope, root: &Path) -> Result<PathBuf> {
+        let base = match scope {
+            ExportScope::Project => root.to_path_buf(),
+            ExportScope::User => dirs::home_dir().context("could not determine home directory")?,
+        };
+        match self {
+            ExportTarget::Claude => Ok(base.join(".claude").join("skills")),
+        }
+    }
+}
+
+/// Skills written by earlier exports, by skill name.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct ExportManifest {
+    skills: BTreeMap<String, ExportedSkill>,
+}
+
+/// A skill written by an export.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct ExportedSkill {
+    /// The SKILL.md it was exported from.
+    source: PathBuf,
+    /// Files written, relative to the skill's directory.
+    files: Vec<PathBuf>,
+    /// Roots of the workspaces whose exports include this skill.
+    #[serde(default)]
+    workspaces: BTreeSet<PathBuf>,
+}
+
+/// Export the skills applicable to the workspace and report what changed.
+pub async fn export_output(
+    target: ExportTarget,
+    scope: ExportScope,
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let dest = target.skills_dir(scope, root)?;
+    let entries: Vec<_> = skills::list(registry, workspace)
+        .await
+        .into_iter()
+        .filter(|entry| entry.is_for_workspace(workspace))
+        .collect();
+    export_skills(&entries, &dest, root, workspace)
+}
+
+/// Write `entries` to the skills directory `dest`, updating the skills
+/// recorded in its manifest and pruning those that no workspace exports
+/// any more.
+fn export_skills(
+    entries: &[SkillWithGroupContext],
+    dest: &Path,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> Result<String> {
+    let manifest_path = dest.join(MANIFEST_FILE);
+    let mut manifest: ExportManifest = match fs::read_to_string(&manifest_path) {
+        Ok(text) => serde_json::from_str(&text)
+            .with_context(|| format!("failed to parse {}", manifest_path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => ExportManifest::default(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", manifest_path.display()));
+        }
+    };
+
+    let mut report = Vec::new();
+    let mut details = None;
+    let mut exported: BTreeMap<String, ExportedSkill> = BTreeMap::new();
+    for entry in entries {
+        let name = entry.skill.name();
+        if !is_skill_name(name) {
+            report.push(format!("skipped {name}: not a valid skill directory name"));
+            continue;
+        }
+        if let Some(first) = exported.get(name) {
+            report.push(format!(
+                "skipped {name} from {}: {} has the same name",
+                entry.skill.path.display(),
+                first.source.display()
+            ));
+            continue;
+        }
+        let skill_dir = dest.join(name);
+        let previous = manifest.skills.remove(name);
+        if previous.is_none() && skill_dir.exists() {
+            report.push(format!(
+                "skipped {name}: {} was not written by symposium",
+                skill_dir.display()
+            ));
+            continue;
+        }
+
+        let body = if entry.skill.template {
+            let details =
+                details.get_or_insert_with(|| crate::crate_sources::workspace_details(root));
+            entry.inlined_body(details, workspace)
+        } else {
+            entry.skill.body.clone()
+        };
+        let files = write_skill(entry, &skill_dir, body)
+            .with_context(|| format!("failed to export skill `{name}`"))?;
+        if let Some(previous) = &previous {
+            let stale: Vec<_> = previous
+                .files
+                .iter()
+                .filter(|file| !files.contains(file))
+                .cloned()
+                .collect();
+            remove_files(&skill_dir, &stale)?;
+        }
+        let (action, mut workspaces) = match previous {
+            Some(previous) => ("updated", previous.workspaces),
+            None => ("added", BTreeSet::new()),
+        };
+        workspaces.insert(root.to_path_buf());
+        report.push(format!("{action} {name}"));
+        exported.insert(
+            name.to_string(),
+            ExportedSkill {
+                source: entry.skill.path.clone(),
+                files,
+                workspaces,
+            },
+        );
+    }
+    let count = exported.len();
+
+    // Whatever is left in the manifest was exported before but no longer
+    // applies to this workspace. Skills other workspaces still export stay.
+    for (name, mut stale) in std::mem::take(&mut manifest.skills) {
+        stale.workspaces.remove(root);
+        if !stale.workspaces.is_empty() {
+            exported.insert(name, stale);
+            continue;
+        }
+        if !is_skill_name(&name) {
+            continue;
+        }
+        remove_files(&dest.join(&name), &stale.files)?;
+        report.push(format!("removed {name}"));
+    }
+
+    manifest.skills = exported;
+    if manifest.skills.is_empty() {
+        if manifest_path.exists() {
+            fs::remove_file(&manifest_path)
+                .with_context(|| format!("failed to remove {}", manifest_path.display()))?;
+        }
+    } else {
+        fs::create_dir_all(dest)?;
+        fs::write(
+            &manifest_path,
+            serde_json::to_string_pretty(&manifest)? + "\n",
+        )
+        .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+    }
+
+    let mut out = format!("Exported {count} skill(s) to {}\n", dest.display());
+    for line in report {
+        out.push_str(&format!("  {line}\n"));
+    }
+    Ok(out)
+}
+
+/// Write a skill's SKILL.md (with `body`) and copies of its resources to
+/// `skill_dir`, returning the files written.
+fn write_skill(
+    entry: &SkillWithGroupContext,
+    skill_dir: &Path,
+    body: String,
+) -> Result<Vec<PathBuf>> {
+    let skill = &entry.skill;
+    fs::create_dir_all(skill_dir)?;
+
+    // Only the fields the agent reads; the rest only matter to symposium.
+    let mut description = skill
+        .frontmatter
+        .get("description")
+        .cloned()
+        .unwrap_or_default();
+    let crate_names = entry.effective_crate_names();
+    if !crate_names.is_empty() {
+        if !description.is_empty() {
+            if !description.ends_with(['.', '!', '?']) {
+                description.push('.');
+            }
+            description.push(' ');
+        }
+        description.push_str(&format!("For code using {}.", crate_names.join(", ")));
+    }
+    let mut frontmatter = serde_yaml_ng::Mapping::new();
+    frontmatter.insert("name".into(), skill.name().into());
+    frontmatter.insert("description".into(), description.into());
+    let content = format!(
+        "---\n{}---\n\n<!-- Exported by `symposium skills export` from {}; changes here are overwritten. -->\n\n{}",
+        serde_yaml_ng::to_string(&frontmatter)?,
+        skill.path.display(),
+        body.trim_start(),
+    );
+    fs::write(skill_dir.join("SKILL.md"), content)?;
+
+    let mut files = vec![PathBuf::from("SKILL.md")];
+    let source_dir = skill.path.parent().unwrap_or(&skill.path);
+    for resource in skills::list_skill_resources(source_dir) {
+        let target = skill_dir.join(&resource);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source_dir.join(&resource), &target)
+            .with_context(|| format!("failed to copy {resource}"))?;
+        files.push(PathBuf::from(resource));
+    }
+    Ok(files)
+}
+
+/// Remove `files` (relative to `skill_dir`) and any directories left empty,
+/// including `skill_dir` itself.
+fn remove_files(skill_dir: &Path, files: &[PathBuf]) -> Result<()> {
+    for file in files {
+        if !file
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!(
+                "refusing to remove `{}` outside {}",
+                file.display(),
+                skill_dir.display()
+            );
+        }
+        let path = skill_dir.join(file);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to remove {}", path.display()));
+            }
+        }
+        // Directories that still hold other files are kept.
+        let mut dir = path.parent();
+        while let Some(current) = dir
+            && current.starts_with(skill_dir)
+            && fs::remove_dir(current).is_ok()
+        {
+            dir = current.parent();
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` can be used as a skill directory name.
+fn is_skill_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::StandaloneSkill;
+
+    fn write_skill_source(dir: &Path, name: &str) -> PathBuf {
+        let skill_dir = dir.join(name);
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            format!(
+                "---\nname: {name}\ndescription: About {name}\ncrates: serde\n---\n\nBody of {name}.\n"
+            ),
+        )
+        .unwrap();
+        skill_dir
+    }
+
+    async fn entries(dir: &Path, names: &[&str]) -> Vec<SkillWithGroupContext> {
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: names
+                .iter()
+                .map(|name| StandaloneSkill {
+                    source: "mine".to_string(),
+                    precedence: Default::default(),
+                    skill: skills::load_standalone_skill(&dir.join(name).join("SKILL.md"), dir)
+                        .unwrap(),
+                })
+                .collect(),
+        };
+        let workspace = [("serde".to_string(), semver::Version::new(1, 0, 0))];
+        skills::list(&registry, &workspace).await
+    }
+
+    #[tokio::test]
+    async fn export_updates_and_prunes_own_skills() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        let dest = tmp.path().join(".claude/skills");
+        let workspace = [("serde".to_string(), semver::Version::new(1, 0, 0))];
+
+        let derive_dir = write_skill_source(&source, "serde-derive");
+        fs::create_dir_all(derive_dir.join("examples")).unwrap();
+        fs::write(
+            derive_dir.join("examples/derive.rs"),
+            "#[derive(Serialize)]\n",
+        )
+        .unwrap();
+        write_skill_source(&source, "serde-attrs");
+        fs::create_dir_all(dest.join("serde-attrs")).unwrap();
+        fs::write(dest.join("serde-attrs/SKILL.md"), "hand-written\n").unwrap();
+
+        let all = entries(&source, &["serde-derive", "serde-attrs"]).await;
+        let report = export_skills(&all, &dest, tmp.path(), &workspace).unwrap();
+        assert!(report.contains("  added serde-derive\n"), "{report}");
+        assert!(report.contains("  skipped serde-attrs:"), "{report}");
+        let exported = fs::read_to_string(dest.join("serde-derive/SKILL.md")).unwrap();
+        assert!(exported.starts_with(
+            "---\nname: serde-derive\ndescription: About serde-derive. For code using serde.\n---\n"
+        ));
+        assert!(exported.ends_with("\n\nBody of serde-derive.\n"));
+        assert!(dest.join("serde-derive/examples/derive.rs").exists());
+        assert_eq!(
+            fs::read_to_string(dest.join("serde-attrs/SKILL.md")).unwrap(),
+            "hand-written\n"
+        );
+
+        // A resource removed at the source is removed from the export.
+        fs::remove_dir_all(derive_dir.join("examples")).unwrap();
+        let report = export_skills(&all, &dest, tmp.path(), &workspace).unwrap();
+        assert!(report.contains("  updated serde-derive\n"), "{report}");
+        assert!(!dest.join("serde-derive/examples").exists());
+
+        // A skill that no longer applies is removed; hand-written ones stay.
+        let report = export_skills(&[], &dest, tmp.path(), &workspace).unwrap();
+        assert!(report.contains("  removed serde-derive\n"), "{report}");
+        assert!(!dest.join("serde-derive").exists());
+        assert!(!dest.join(MANIFEST_FILE).exists());
+        assert!(dest.join("serde-attrs/SKILL.md").exists());
+    }
+
+    #[tokio::test]
+    async fn export_keeps_skills_of_other_workspaces() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        let dest = tmp.path().join("home/.claude/skills");
+        let (first, second) = (tmp.path().join("first"), tmp.path().join("second"));
+        let workspace = [("serde".to_string(), semver::Version::new(1, 0, 0))];
+        write_skill_source(&source, "serde-derive");
+        write_skill_source(&source, "serde-attrs");
+        let derive = entries(&source, &["serde-derive"]).await;
+        let attrs = entries(&source, &["serde-attrs"]).await;
+
+        export_skills(&derive, &dest, &first, &workspace).unwrap();
+        let report = export_skills(&attrs, &dest, &second, &workspace).unwrap();
+        assert!(!report.contains("removed"), "{report}");
+        assert!(dest.join("serde-derive/SKILL.md").exists());
+        assert!(dest.join("serde-attrs/SKILL.md").exists());
+
+        // A skill is removed once no workspace exports it.
+        let report = export_skills(&[], &dest, &first, &workspace).unwrap();
+        assert!(report.contains("  removed serde-derive\n"), "{report}");
+        assert!(dest.join("serde-attrs/SKILL.md").exists());
+    }
+
+    #[tokio::test]
+    async fn export_reports_duplicate_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join(".claude/skills");
+        let workspace = [("serde".to_string(), semver::Version::new(1, 0, 0))];
+        write_skill_source(&tmp.path().join("one"), "serde-derive");
+        write_skill_source(&tmp.path().join("two"), "serde-derive");
+        let mut all = entries(&tmp.path().join("one"), &["serde-derive"]).await;
+        all.extend(entries(&tmp.path().join("two"), &["serde-derive"]).await);
+
+        let report = export_skills(&all, &dest, tmp.path(), &workspace).unwrap();
+        assert!(report.contains("  added serde-derive\n"), "{report}");
+        assert!(
+            report.contains(&format!(
+                "  skipped serde-derive from {}: {} has the same name\n",
+                tmp.path().join("two/serde-derive/SKILL.md").display(),
+                tmp.path().join("one/serde-derive/SKILL.md").display(),
+            )),
+            "{report}"
+        );
+        assert!(!report.contains("not written by symposium"), "{report}");
+    }
+}
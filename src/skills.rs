@@ -8,6 +8,8 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 
+use crate::config::Precedence;
+use crate::crate_sources::WorkspaceDetails;
 use crate::predicate::{self, Predicate};
 use crate::plugins::{ParsedPlugin, PluginRegistry, SkillGroup};
 
@@ -16,24 +18,100 @@ pub async fn list_output(
     registry: &PluginRegistry,
     workspace: &[(String, semver::Version)],
 ) -> String {
-    let skills = list(registry, workspace).await;
-    if skills.is_empty() {
-        "No skills available for crates in the current dependencies.".to_string()
+    let resolved = resolve_skills(registry, None, false, workspace).await;
+    let (rust_skills, crate_skills): (Vec<_>, Vec<_>) = resolved
+        .skills
+        .into_iter()
+        .partition(|entry| entry.skill.scope == Scope::Rust);
+
+    let mut out = String::new();
+    if crate_skills.is_empty() {
+        out.push_str("No skills available for crates in the current dependencies.\n");
     } else {
-        let mut out = "Skills available for crates in the current dependencies:\n\n".to_string();
-        for entry in &skills {
+        out.push_str("Skills available for crates in the current dependencies:\n\n");
+        for entry in &crate_skills {
             let crate_names = entry.effective_crate_names();
             out.push_str(&format_skill_entry(&entry.skill, &crate_names));
         }
-        out
     }
+    if !rust_skills.is_empty() {
+        out.push_str("\nGeneral Rust skills (use `rust guidance [topic]`):\n\n");
+        for entry in &rust_skills {
+            out.push_str(&format_skill_entry(&entry.skill, &[]));
+        }
+    }
+    if !resolved.shadowed.is_empty() {
+        out.push_str("\nShadowed skills (not used):\n\n");
+        for shadowed in &resolved.shadowed {
+            let reason = if shadowed.overridden {
+                "overridden by"
+            } else {
+                "shadowed by"
+            };
+            out.push_str(&format!(
+                "- **{}** from `{}`: {reason} `{}`\n",
+                shadowed.entry.skill.name(),
+                shadowed.entry.origin.plugin,
+                shadowed.by,
+            ));
+        }
+    }
+    out
+}
+
+/// Format general (Rust-scoped) guidance, optionally restricted to one topic.
+pub async fn rust_guidance_output(
+    topic: Option<&str>,
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> String {
+    let entries: Vec<_> = list(registry, workspace)
+        .await
+        .into_iter()
+        .filter(|entry| entry.skill.scope == Scope::Rust)
+        .collect();
+
+    let topics: std::collections::BTreeSet<_> = entries
+        .iter()
+        .flat_map(|entry| entry.skill.topics.iter().map(|t| t.to_lowercase()))
+        .collect();
+    let selected: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| topic.is_none_or(|topic| entry.skill.covers_topic(topic)))
+        .collect();
+
+    if selected.is_empty() {
+        let mut out = match topic {
+            Some(topic) => format!("No general Rust guidance for topic `{topic}`.\n"),
+            None => "No general Rust guidance available.\n".to_string(),
+        };
+        if !topics.is_empty() {
+            let topics: Vec<_> = topics.into_iter().collect();
+            out.push_str(&format!("Topics: {}\n", topics.join(", ")));
+        }
+        return out;
+    }
+
+    let mut out = match topic {
+        Some(topic) => format!("Rust guidance on `{topic}`\n"),
+        None => "Rust guidance\n".to_string(),
+    };
+    let advice = advice_from(selected, Vec::new(), None, root, workspace).await;
+    out.push_str(&advice.format_output(&GuidanceOptions::configured()));
+    out
 }
 
 /// Fetch crate sources and format info with any matching guidance.
+///
+/// `options` control how the guidance is formatted; without a budget, the
+/// configured default applies.
 pub async fn info_output(
     name: &str,
     version: Option<&str>,
+    options: GuidanceOptions,
     registry: &PluginRegistry,
+    root: &Path,
     workspace: &[(String, semver::Version)],
 ) -> anyhow::Result<String> {
     let mut fetch = crate::crate_sources::RustCrateFetch::new(name, workspace);
@@ -50,9 +128,14 @@ pub async fn info_output(
         result.path.display()
     );
 
-    let advice = guidance(&result.name, registry, workspace).await;
+    let version = semver::Version::parse(&result.version.to_string()).ok();
+    let advice = guidance(&result.name, version.as_ref(), registry, root, workspace).await;
     if !advice.is_empty() {
-        output.push_str(&advice.format_output());
+        let options = GuidanceOptions {
+            budget: options.budget.or_else(crate::config::guidance_budget),
+            ..options
+        };
+        output.push_str(&advice.format_output(&options));
     }
 
     Ok(output)
@@ -67,6 +150,90 @@ pub enum Activation {
     /// Skill is listed with its path for on-demand loading.
     #[default]
     Optional,
+    /// Skill is listed like an optional skill, and inlined by the
+    /// `PreToolUse` hook when a tool call matches its [`Triggers`].
+    Triggers,
+}
+
+/// When a skill with `activation: triggers` is activated.
+///
+/// Each kind of trigger that is given must match: the tool must be one of
+/// `tools`, the file it reads or edits must match one of `files`, and that
+/// file (or the text written to it) must contain one of `patterns`.
+#[derive(Debug, Clone, Default)]
+pub struct Triggers {
+    /// Globs for the file, relative to the workspace root (e.g.
+    /// `migrations/**/*.sql`).
+    pub files: Vec<String>,
+    /// Tool names (e.g. `Edit`).
+    pub tools: Vec<String>,
+    /// Text to look for in the file (e.g. `#[tokio::main]`).
+    pub patterns: Vec<String>,
+}
+
+impl Triggers {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.tools.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Check whether `tool_use` activates the skill.
+    pub fn matches(&self, tool_use: &ToolUse) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        if !self.tools.is_empty() && !self.tools.contains(&tool_use.tool) {
+            return false;
+        }
+        if !self.files.is_empty() {
+            let Some(file) = &tool_use.file else {
+                return false;
+            };
+            match file_globs(&self.files) {
+                Ok(globs) if globs.is_match(file) => {}
+                Ok(_) => return false,
+                Err(e) => {
+                    tracing::warn!(error = %e, "invalid trigger glob");
+                    return false;
+                }
+            }
+        }
+        self.patterns.is_empty()
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| tool_use.text.contains(pattern))
+    }
+}
+
+/// A tool call, as seen by the hook, for matching [`Triggers`].
+#[derive(Debug, Clone, Default)]
+pub struct ToolUse {
+    /// Tool name (e.g. `Read`).
+    pub tool: String,
+    /// File the tool reads or edits, relative to the workspace root when it
+    /// is inside it.
+    pub file: Option<PathBuf>,
+    /// Text of the file, and any text being written to it.
+    pub text: String,
+}
+
+/// Compile trigger file globs.
+fn file_globs(globs: &[String]) -> Result<globset::GlobSet> {
+    let mut set = globset::GlobSetBuilder::new();
+    for glob in globs {
+        set.add(globset::Glob::new(glob).with_context(|| format!("invalid glob `{glob}`"))?);
+    }
+    Ok(set.build()?)
+}
+
+/// What a skill gives guidance on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scope {
+    /// Guidance on specific crates, selected by `crates` predicates.
+    #[default]
+    Crate,
+    /// General Rust guidance, independent of any crate (`scope: rust`).
+    Rust,
 }
 
 /// A parsed skill from a SKILL.md file.
@@ -80,6 +247,26 @@ pub struct Skill {
     pub applies_when: Vec<Predicate>,
     /// Activation mode.
     pub activation: Activation,
+    /// Whether this is crate guidance or general Rust guidance.
+    pub scope: Scope,
+    /// Topics covered by a Rust-scoped skill (e.g. `error-handling`).
+    pub topics: Vec<String>,
+    /// Error codes and lint names this skill helps with (e.g. `E0277`,
+    /// `clippy::needless_return`), normalized by [`normalize_diagnostic`].
+    pub diagnostics: Vec<String>,
+    /// Skills this one replaces, as `<plugin>/<skill>`.
+    pub overrides: Vec<String>,
+    /// Family of alternative variants this skill belongs to; only the most
+    /// specific matching variant of a family is used.
+    pub family: Option<String>,
+    /// Whether the body is a minijinja template rendered against the
+    /// workspace when it is inlined.
+    pub template: bool,
+    /// Tool calls that activate the skill, for `activation: triggers`.
+    pub triggers: Triggers,
+    /// Names of other skills this one assumes; they are shown with it as
+    /// related guidance.
+    pub requires: Vec<String>,
     /// The body content (everything after frontmatter).
     pub body: String,
     /// Path to the SKILL.md file on disk.
@@ -91,8 +278,22 @@ impl Skill {
     ///
     /// Returns `true` if any skill-level `crates` predicate references the crate,
     /// or if the skill has no skill-level `crates` (inheriting from the group).
+    /// Rust-scoped skills never advise on a crate.
     pub fn advises_on(&self, crate_name: &str) -> bool {
-        self.crates.is_empty() || self.crates.iter().any(|p| p.references_crate(crate_name))
+        self.scope == Scope::Crate
+            && (self.crates.is_empty()
+                || self.crates.iter().any(|p| p.references_crate(crate_name)))
+    }
+
+    /// Check whether this skill is tagged for an error code or lint name.
+    pub fn covers_diagnostic(&self, diagnostic: &str) -> bool {
+        let diagnostic = normalize_diagnostic(diagnostic);
+        self.diagnostics.contains(&diagnostic)
+    }
+
+    /// Check whether this Rust-scoped skill covers `topic` (case-insensitive).
+    pub fn covers_topic(&self, topic: &str) -> bool {
+        self.topics.iter().any(|t| t.eq_ignore_ascii_case(topic))
     }
 
     /// Check whether this skill's `applies-when` constraints match the workspace.
@@ -126,65 +327,337 @@ pub struct CrateAdvice {
     pub default_content: Vec<(String, PathBuf, String)>,
     /// Optional skills with full metadata for agent decision-making.
     pub optional_skills: Vec<Skill>,
+    /// Body content of skills required by the inlined skills (skill name,
+    /// path, body text).
+    pub related_content: Vec<(String, PathBuf, String)>,
+    /// Skills found through a crate related to the queried one, by skill
+    /// name, with that crate.
+    pub related_crates: BTreeMap<String, String>,
 }
 
 impl CrateAdvice {
     pub fn is_empty(&self) -> bool {
-        self.default_content.is_empty() && self.optional_skills.is_empty()
+        self.default_content.is_empty()
+            && self.optional_skills.is_empty()
+            && self.related_content.is_empty()
     }
 
     /// Format the advice as text to append to crate command output.
     ///
     /// Uses `<skill_content>` structured wrapping as recommended by
     /// https://agentskills.io/client-implementation/adding-skills-support#structured-wrapping
-    pub fn format_output(&self) -> String {
+    ///
+    /// Skills required by the inlined skills follow them under "Related
+    /// guidance".
+    ///
+    /// With a budget (in estimated tokens, see [`estimate_tokens`]),
+    /// always-on and related skills are inlined in order while they fit and the rest are
+    /// demoted to a list of paths to load on demand; optional skills are
+    /// listed with whatever budget remains, and resource listings are capped
+    /// at [`BUDGETED_RESOURCE_LIMIT`] files per skill. Anything left out is
+    /// reported at the end.
+    pub fn format_output(&self, options: &GuidanceOptions) -> String {
+        let budget = options.budget;
+        let mut remaining = budget.unwrap_or(usize::MAX);
+        let resource_limit = budget.map(|_| BUDGETED_RESOURCE_LIMIT);
         let mut out = String::new();
 
-        if !self.default_content.is_empty() {
+        let mut demoted = Vec::new();
+        let inlined = inline_skills(
+            &self.default_content,
+            &self.related_crates,
+            options,
+            resource_limit,
+            &mut remaining,
+            &mut demoted,
+        );
+        if !inlined.is_empty() {
             out.push_str("\n## Guidance\n");
-            for (name, path, body) in &self.default_content {
-                let skill_dir = path.parent().unwrap_or(path);
+            out.push_str(&inlined);
+        }
+        let related = inline_skills(
+            &self.related_content,
+            &self.related_crates,
+            options,
+            resource_limit,
+            &mut remaining,
+            &mut demoted,
+        );
+        if !related.is_empty() {
+            out.push_str("\n## Related guidance\n\nSkills the guidance above builds on:\n");
+            out.push_str(&related);
+        }
+
+        let mut listed = String::new();
+        let mut unlisted = 0;
+        for skill in &self.optional_skills {
+            let mut entry = format_skill_entry(skill, &skill.crate_names());
+            if let Some(related_crate) = self.related_crates.get(skill.name()) {
+                entry.push_str(&format!("  - From related crate: {related_crate}\n"));
+            }
+            let cost = estimate_tokens(&entry);
+            if cost <= remaining {
+                remaining -= cost;
+                listed.push_str(&entry);
+            } else {
+                unlisted += 1;
+            }
+        }
+        if !listed.is_empty() {
+            out.push_str("\n## Additional skills available\n\n");
+            out.push_str(&listed);
+        }
+
+        if !demoted.is_empty() || unlisted > 0 {
+            out.push_str(&format!(
+                "\n## Omitted guidance\n\nOutput is limited to about {} tokens.\n",
+                budget.unwrap_or_default()
+            ));
+            if !demoted.is_empty() {
+                out.push_str(
+                    "These skills were not inlined; read their SKILL.md for the guidance:\n\n",
+                );
+                for (name, path) in demoted {
+                    out.push_str(&format!("- **{name}**: {}\n", path.display()));
+                }
+            }
+            if unlisted > 0 {
                 out.push_str(&format!(
-                    "\n<skill_content name=\"{name}\">\n\
-                     \n{body}\n\
-                     \nSkill directory: {}\n\
-                     Relative paths in this skill are relative to the skill directory.\n",
-                    skill_dir.display()
+                    "{unlisted} additional skill(s) were not listed.\n"
                 ));
+            }
+        }
 
-                let resources = list_skill_resources(skill_dir);
-                if !resources.is_empty() {
-                    out.push_str("\n<skill_resources>\n");
-                    for resource in &resources {
-                        out.push_str(&format!("  <file>{resource}</file>\n"));
-                    }
-                    out.push_str("</skill_resources>\n");
-                }
+        out
+    }
+}
 
-                out.push_str("</skill_content>\n");
+/// Inline skill bodies in order while they fit in `remaining`, pushing the
+/// ones that don't onto `demoted`. Skills in `related_crates` are labelled
+/// with the related crate.
+fn inline_skills<'a>(
+    content: &'a [(String, PathBuf, String)],
+    related_crates: &BTreeMap<String, String>,
+    options: &GuidanceOptions,
+    resource_limit: Option<usize>,
+    remaining: &mut usize,
+    demoted: &mut Vec<(&'a String, &'a PathBuf)>,
+) -> String {
+    let mut inlined = String::new();
+    for (name, path, body) in content {
+        let related_crate = related_crates.get(name).map(String::as_str);
+        let content = format_skill_content(name, path, body, related_crate, resource_limit);
+        let cost = estimate_tokens(&content);
+        if cost <= *remaining {
+            *remaining -= cost;
+            inlined.push_str(&content);
+            if options.with_resources {
+                let skill_dir = path.parent().unwrap_or(path);
+                inlined.push_str(&inline_resources(skill_dir, remaining));
             }
+            inlined.push_str("</skill_content>\n");
+        } else {
+            demoted.push((name, path));
         }
+    }
+    inlined
+}
 
-        if !self.optional_skills.is_empty() {
-            out.push_str("\n## Additional skills available\n\n");
-            for skill in &self.optional_skills {
-                let crate_names = skill.crate_names();
-                out.push_str(&format_skill_entry(skill, &crate_names));
+/// Options for formatting [`CrateAdvice`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GuidanceOptions {
+    /// Approximate token limit for the output, if any.
+    pub budget: Option<usize>,
+    /// Include the text resources of inlined skills in `<skill_resource>` blocks.
+    pub with_resources: bool,
+}
+
+impl GuidanceOptions {
+    /// Options with the budget from `config.toml`.
+    pub fn configured() -> Self {
+        Self {
+            budget: crate::config::guidance_budget(),
+            with_resources: false,
+        }
+    }
+}
+
+/// Resource files listed per skill when guidance output has a budget.
+const BUDGETED_RESOURCE_LIMIT: usize = 20;
+
+/// Largest resource file inlined by `with_resources`.
+const MAX_INLINE_RESOURCE_BYTES: u64 = 16 * 1024;
+
+/// Most resource text inlined for a single skill by `with_resources`.
+const MAX_INLINE_RESOURCES_PER_SKILL: usize = 64 * 1024;
+
+/// Rough token count for budgeting output: about four bytes per token.
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Format one inlined skill, up to but not including its closing
+/// `</skill_content>` tag, listing at most `resource_limit` resource files.
+fn format_skill_content(
+    name: &str,
+    path: &Path,
+    body: &str,
+    related_crate: Option<&str>,
+    resource_limit: Option<usize>,
+) -> String {
+    let skill_dir = path.parent().unwrap_or(path);
+    let related = related_crate
+        .map(|related_crate| format!(" related-crate=\"{related_crate}\""))
+        .unwrap_or_default();
+    let mut out = format!(
+        "\n<skill_content name=\"{name}\"{related}>\n\
+         \n{body}\n\
+         \nSkill directory: {}\n\
+         Relative paths in this skill are relative to the skill directory.\n",
+        skill_dir.display()
+    );
+
+    let resources = list_skill_resources(skill_dir);
+    if !resources.is_empty() {
+        let shown = resource_limit.unwrap_or(usize::MAX).min(resources.len());
+        out.push_str("\n<skill_resources>\n");
+        for resource in &resources[..shown] {
+            out.push_str(&format!("  <file>{resource}</file>\n"));
+        }
+        if shown < resources.len() {
+            out.push_str(&format!("  ({} more files)\n", resources.len() - shown));
+        }
+        out.push_str("</skill_resources>\n");
+    }
+
+    out
+}
+
+/// Inline the text resources in a skill directory as `<skill_resource>`
+/// blocks, charging them to `remaining`.
+///
+/// Binary files, files over [`MAX_INLINE_RESOURCE_BYTES`], and files past
+/// [`MAX_INLINE_RESOURCES_PER_SKILL`] or the remaining budget are named in a
+/// note instead.
+fn inline_resources(skill_dir: &Path, remaining: &mut usize) -> String {
+    let mut out = String::new();
+    let mut skipped = Vec::new();
+    let mut total = 0;
+
+    for resource in list_skill_resources(skill_dir) {
+        let text = match read_text_resource(&skill_dir.join(&resource)) {
+            Ok(text) => text,
+            Err(reason) => {
+                skipped.push(format!("{resource} ({reason})"));
+                continue;
             }
+        };
+        let mut block = format!("\n<skill_resource path=\"{resource}\">\n{text}");
+        if !block.ends_with('\n') {
+            block.push('\n');
         }
+        block.push_str("</skill_resource>\n");
 
-        out
+        let cost = estimate_tokens(&block);
+        if total + text.len() > MAX_INLINE_RESOURCES_PER_SKILL || cost > *remaining {
+            skipped.push(format!("{resource} (over the size limit)"));
+            continue;
+        }
+        total += text.len();
+        *remaining -= cost;
+        out.push_str(&block);
+    }
+
+    if !skipped.is_empty() {
+        out.push_str(&format!(
+            "\nResources not inlined: {}\n",
+            skipped.join(", ")
+        ));
+    }
+    out
+}
+
+/// Read a resource file if it is small enough to inline and looks like text.
+fn read_text_resource(path: &Path) -> Result<String, &'static str> {
+    let metadata = std::fs::symlink_metadata(path).map_err(|_| "unreadable")?;
+    if !metadata.is_file() {
+        return Err("not a regular file");
+    }
+    let len = metadata.len();
+    if len > MAX_INLINE_RESOURCE_BYTES {
+        return Err("too large");
     }
+    let bytes = std::fs::read(path).map_err(|_| "unreadable")?;
+    if bytes.contains(&0) {
+        return Err("binary");
+    }
+    String::from_utf8(bytes).map_err(|_| "binary")
 }
 
 /// A skill paired with its group's crate predicates, for display purposes.
+#[derive(Clone)]
 pub struct SkillWithGroupContext {
     pub skill: Skill,
     /// Group-level crate predicates (used when the skill has none of its own).
     pub group_crates: Vec<Predicate>,
+    /// The plugin (or source, for standalone skills) providing the skill.
+    pub origin: SkillOrigin,
+    /// The related crate the skill was found through, when it is not for the
+    /// queried crate itself.
+    pub related_crate: Option<String>,
+}
+
+/// Where a resolved skill comes from.
+#[derive(Debug, Clone)]
+pub struct SkillOrigin {
+    /// Plugin name, or plugin source name for standalone skills.
+    pub plugin: String,
+    pub precedence: Precedence,
+}
+
+/// A skill left out because another skill takes its place.
+pub struct ShadowedSkill {
+    pub entry: SkillWithGroupContext,
+    /// The skill used instead, as `<plugin>/<skill>`.
+    pub by: String,
+    /// True if `by` names this skill in `overrides`; otherwise `by` has the
+    /// same name and comes from a higher-precedence (or earlier) source.
+    pub overridden: bool,
+}
+
+/// Skills resolved for a query, after precedence is applied.
+pub struct ResolvedSkills {
+    pub skills: Vec<SkillWithGroupContext>,
+    pub shadowed: Vec<ShadowedSkill>,
 }
 
 impl SkillWithGroupContext {
+    /// The skill's name qualified by its plugin: `<plugin>/<skill>`.
+    pub fn qualified_name(&self) -> String {
+        format!("{}/{}", self.origin.plugin, self.skill.name())
+    }
+
+    /// Check the version constraints of the `crates` predicates (skill and
+    /// group level) that name a workspace crate against its resolved version.
+    fn matches_resolved_versions(&self, workspace: &[(String, semver::Version)]) -> bool {
+        self.group_crates
+            .iter()
+            .chain(&self.skill.crates)
+            .filter(|p| workspace.iter().any(|(name, _)| p.references_crate(name)))
+            .all(|p| p.matches(workspace))
+    }
+
+    /// Sum of [`Predicate::specificity`] over the skill's and its group's
+    /// predicates, used to choose between variants of a skill family.
+    fn specificity(&self) -> u32 {
+        self.group_crates
+            .iter()
+            .chain(&self.skill.crates)
+            .chain(&self.skill.applies_when)
+            .map(Predicate::specificity)
+            .sum()
+    }
+
     /// Return the effective crate names this skill applies to.
     ///
     /// Uses skill-level crates if present, otherwise falls back to group-level.
@@ -200,6 +673,33 @@ impl SkillWithGroupContext {
         }
         names.into_iter().collect()
     }
+
+    /// Check whether this is a Rust-scoped skill or a skill for a crate in
+    /// the workspace.
+    pub fn is_for_workspace(&self, workspace: &[(String, semver::Version)]) -> bool {
+        self.skill.scope == Scope::Rust
+            || self
+                .effective_crate_names()
+                .iter()
+                .any(|name| workspace.iter().any(|(dep, _)| dep == name))
+    }
+
+    /// The body as it is inlined in guidance. A templated body is rendered
+    /// for the first of the skill's crates in the workspace.
+    pub fn inlined_body(
+        &self,
+        details: &WorkspaceDetails,
+        workspace: &[(String, semver::Version)],
+    ) -> String {
+        if !self.skill.template {
+            return self.skill.body.clone();
+        }
+        let for_crate = self
+            .effective_crate_names()
+            .into_iter()
+            .find(|name| workspace.iter().any(|(dep, _)| dep == name));
+        render_body(&self.skill, for_crate.as_deref(), details, workspace)
+    }
 }
 
 /// Resolve all applicable skills from the registry.
@@ -209,119 +709,635 @@ impl SkillWithGroupContext {
 /// the workspace (used for listing).
 ///
 /// Both group-level and skill-level `applies-when` constraints must match the workspace.
+///
+/// With `include_related`, skills for the crates plugins declare as related
+/// to `for_crate` (see [`PluginRegistry::related_crates`]) are included too,
+/// when those crates are in the workspace. They are marked with the related
+/// crate they were found through.
+///
+/// Only one skill of each name is kept; see [`apply_precedence`]. Of skills
+/// sharing a `family`, only the most specific match is kept; see
+/// [`select_family_variants`].
 async fn resolve_skills(
     registry: &PluginRegistry,
     for_crate: Option<&str>,
+    include_related: bool,
     workspace: &[(String, semver::Version)],
-) -> Vec<SkillWithGroupContext> {
+) -> ResolvedSkills {
     let mut results = Vec::new();
+    collect_registry_skills(registry, for_crate, workspace, &mut results).await;
+
+    if include_related && let Some(crate_name) = for_crate {
+        for related_crate in registry.related_crates(crate_name) {
+            if !workspace.iter().any(|(name, _)| *name == related_crate) {
+                continue;
+            }
+            let mut found = Vec::new();
+            collect_registry_skills(registry, Some(&related_crate), workspace, &mut found).await;
+            for mut entry in found {
+                if results
+                    .iter()
+                    .any(|known| known.qualified_name() == entry.qualified_name())
+                {
+                    continue;
+                }
+                entry.related_crate = Some(related_crate.clone());
+                results.push(entry);
+            }
+        }
+    }
+
+    let mut resolved = apply_precedence(results);
+    resolved.skills = select_family_variants(resolved.skills, workspace);
+    resolved
+}
 
+/// Collect the skills from plugin manifests and standalone skills that match
+/// `for_crate` (if any) and the workspace.
+async fn collect_registry_skills(
+    registry: &PluginRegistry,
+    for_crate: Option<&str>,
+    workspace: &[(String, semver::Version)],
+    results: &mut Vec<SkillWithGroupContext>,
+) {
     // Skills from plugin manifests. We iterate these separately
     // because we lazily load skill groups, so there
     // is extra logic.
-    for ParsedPlugin { path, plugin } in &registry.plugins {
+    for ParsedPlugin {
+        path,
+        plugin,
+        precedence,
+    } in &registry.plugins
+    {
+        let origin = SkillOrigin {
+            plugin: plugin.name.clone(),
+            precedence: *precedence,
+        };
         for group in &plugin.skills {
             let (group_crates, skills) =
                 load_skills_for_group(path, group, for_crate, workspace).await;
 
-            collect_matching_skills(&skills, &group_crates, for_crate, workspace, &mut results);
+            collect_matching_skills(
+                &skills,
+                &group_crates,
+                &origin,
+                for_crate,
+                workspace,
+                results,
+            );
         }
     }
 
     // Standalone skills -- these are already loaded as part of the plugin
     // registry.
-    collect_matching_skills(
-        &registry.standalone_skills,
-        &[],
-        for_crate,
-        workspace,
-        &mut results,
-    );
+    for standalone in &registry.standalone_skills {
+        let origin = SkillOrigin {
+            plugin: standalone.source.clone(),
+            precedence: standalone.precedence,
+        };
+        collect_matching_skills(
+            std::slice::from_ref(&standalone.skill),
+            &[],
+            &origin,
+            for_crate,
+            workspace,
+            results,
+        );
+    }
+}
+
+/// Keep the best-matching variant of each skill family.
+///
+/// Skills sharing a `family` are alternatives, such as `tokio<1.0` and
+/// `tokio>=1.0` versions of the same advice, or a general skill and a
+/// version-specific one. A variant is a candidate only if its `crates`
+/// version constraints accept the versions resolved in the workspace; of
+/// the candidates, the one whose predicates are most specific is kept (the
+/// first loaded, on a tie). Skills without a family are kept as is.
+///
+/// Families are scoped to their plugin, so unrelated plugins that happen to
+/// pick the same family name do not compete.
+fn select_family_variants(
+    entries: Vec<SkillWithGroupContext>,
+    workspace: &[(String, semver::Version)],
+) -> Vec<SkillWithGroupContext> {
+    let family_key = |entry: &SkillWithGroupContext| {
+        let family = entry.skill.family.as_ref()?;
+        Some((entry.origin.plugin.clone(), family.clone()))
+    };
+
+    let mut best: BTreeMap<(String, String), (u32, usize)> = BTreeMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(key) = family_key(entry)
+            && entry.matches_resolved_versions(workspace)
+        {
+            let score = entry.specificity();
+            match best.get(&key) {
+                Some(&(top, _)) if top >= score => {}
+                _ => {
+                    best.insert(key, (score, index));
+                }
+            }
+        }
+    }
+
+    entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, entry)| match (family_key(&entry), &entry.skill.family) {
+            (Some(key), Some(family))
+                if best.get(&key).is_none_or(|&(_, kept)| kept != index) =>
+            {
+                tracing::debug!(skill = %entry.qualified_name(), %family, "less specific family variant skipped");
+                None
+            }
+            _ => Some(entry),
+        })
+        .collect()
+}
+
+/// Keep one skill per name and set of crates advised on.
+///
+/// A skill named in another skill's `overrides` is dropped first. Of the
+/// remaining skills sharing a name and crates, the one from the
+/// highest-precedence source is kept (the first loaded, on a tie). Skills
+/// that share only a name, such as a `getting-started` skill for `tokio` and
+/// one for `serde`, are both kept.
+fn apply_precedence(entries: Vec<SkillWithGroupContext>) -> ResolvedSkills {
+    let mut shadowed = Vec::new();
+
+    let overrides: BTreeMap<String, String> = entries
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .skill
+                .overrides
+                .iter()
+                .map(|target| (target.clone(), entry.qualified_name()))
+        })
+        .collect();
+    let mut remaining = Vec::new();
+    for entry in entries {
+        match overrides.get(&entry.qualified_name()) {
+            Some(by) if *by != entry.qualified_name() => shadowed.push(ShadowedSkill {
+                entry,
+                by: by.clone(),
+                overridden: true,
+            }),
+            _ => remaining.push(entry),
+        }
+    }
+
+    let keys: Vec<(String, Vec<String>)> = remaining
+        .iter()
+        .map(|entry| {
+            (
+                entry.skill.name().to_string(),
+                entry.effective_crate_names(),
+            )
+        })
+        .collect();
+    let mut winners: BTreeMap<&(String, Vec<String>), usize> = BTreeMap::new();
+    for (index, (entry, key)) in remaining.iter().zip(&keys).enumerate() {
+        match winners.get(key) {
+            Some(&best) if remaining[best].origin.precedence >= entry.origin.precedence => {}
+            _ => {
+                winners.insert(key, index);
+            }
+        }
+    }
+    let winner_indices: Vec<usize> = keys.iter().map(|key| winners[key]).collect();
+    let winner_names: Vec<String> = winner_indices
+        .iter()
+        .map(|&best| remaining[best].qualified_name())
+        .collect();
+
+    let mut skills = Vec::new();
+    for (index, (entry, by)) in remaining.into_iter().zip(winner_names).enumerate() {
+        if winner_indices[index] == index {
+            skills.push(entry);
+        } else {
+            tracing::debug!(skill = %entry.qualified_name(), %by, "skill shadowed");
+            shadowed.push(ShadowedSkill {
+                entry,
+                by,
+                overridden: false,
+            });
+        }
+    }
 
-    results
+    ResolvedSkills { skills, shadowed }
 }
 
 /// List skills available for crates in the workspace.
-async fn list(
+pub async fn list(
     registry: &PluginRegistry,
     workspace: &[(String, semver::Version)],
 ) -> Vec<SkillWithGroupContext> {
-    resolve_skills(registry, None, workspace).await
+    resolve_skills(registry, None, false, workspace)
+        .await
+        .skills
 }
 
 /// Get guidance for a specific crate from installed plugin skills.
-async fn guidance(
+///
+/// Skills named in the `requires` of an always-on skill are added as related
+/// guidance; see [`resolve_requires`]. The `requires` of optional skills are
+/// not followed, since their bodies are not inlined.
+///
+/// `version` is the version of the crate being advised on, when it is not
+/// (or not only) the one in the workspace, such as an explicitly requested
+/// version or a crate that is not a dependency. Skills are chosen, and
+/// templates rendered, for that version.
+///
+/// `root` is the workspace directory, read when a templated skill is rendered.
+pub async fn guidance(
     crate_name: &str,
+    version: Option<&semver::Version>,
     registry: &PluginRegistry,
+    root: &Path,
     workspace: &[(String, semver::Version)],
 ) -> CrateAdvice {
-    let mut advice = CrateAdvice {
-        default_content: Vec::new(),
-        optional_skills: Vec::new(),
+    let advised = with_crate_version(workspace, crate_name, version);
+    let skills = resolve_skills(registry, Some(crate_name), true, &advised)
+        .await
+        .skills;
+    let requiring: Vec<_> = skills
+        .iter()
+        .filter(|entry| entry.skill.activation == Activation::Always)
+        .collect();
+    let related = if requiring
+        .iter()
+        .any(|entry| !entry.skill.requires.is_empty())
+    {
+        let available = list(registry, workspace).await;
+        resolve_requires(&requiring, &available)
+    } else {
+        Vec::new()
     };
+    advice_from(skills, related, Some(crate_name), root, &advised).await
+}
 
-    for entry in resolve_skills(registry, Some(crate_name), workspace).await {
-        match entry.skill.activation {
-            Activation::Always => {
-                let name = entry.skill.name().to_string();
-                let path = entry.skill.path.clone();
-                advice
-                    .default_content
-                    .push((name, path, entry.skill.body.clone()));
-            }
-            Activation::Optional => {
-                advice.optional_skills.push(entry.skill);
-            }
-        }
-    }
-
-    advice
+/// `workspace` with `crate_name` at `version` in place of the version
+/// resolved there, if any.
+fn with_crate_version(
+    workspace: &[(String, semver::Version)],
+    crate_name: &str,
+    version: Option<&semver::Version>,
+) -> Vec<(String, semver::Version)> {
+    let Some(version) = version else {
+        return workspace.to_vec();
+    };
+    workspace
+        .iter()
+        .filter(|(name, _)| name != crate_name)
+        .cloned()
+        .chain([(crate_name.to_string(), version.clone())])
+        .collect()
 }
 
-/// Format a skill listing entry for display (shared by list and guidance output).
-/// List resource files in a skill directory, as paths relative to that directory.
+/// Collect the skills transitively required by `requiring`.
 ///
-/// Excludes `SKILL.md` itself. Returns sorted paths for deterministic output.
-fn list_skill_resources(skill_dir: &Path) -> Vec<String> {
-    let mut resources = Vec::new();
-    collect_resources_recursive(skill_dir, skill_dir, &mut resources);
-    resources.sort();
-    resources
+/// Required skills are looked up by name in `available`. Skills in
+/// `requiring` are already inlined and are not repeated, and each required
+/// skill is added once, in the order first required. A required skill that
+/// is not available, or that would close a cycle, is logged and skipped.
+fn resolve_requires(
+    requiring: &[&SkillWithGroupContext],
+    available: &[SkillWithGroupContext],
+) -> Vec<SkillWithGroupContext> {
+    let mut seen: std::collections::BTreeSet<String> = requiring
+        .iter()
+        .map(|entry| entry.skill.name().to_string())
+        .collect();
+    let mut related = Vec::new();
+    for entry in requiring {
+        let mut chain = vec![entry.skill.name().to_string()];
+        require_skills(&entry.skill, available, &mut chain, &mut seen, &mut related);
+    }
+    related
 }
 
-fn collect_resources_recursive(base: &Path, dir: &Path, out: &mut Vec<String>) {
-    let entries = match std::fs::read_dir(dir) {
-        Ok(e) => e,
-        Err(_) => return,
-    };
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            collect_resources_recursive(base, &path, out);
-        } else if path.file_name().is_some_and(|f| f != "SKILL.md") {
-            if let Ok(relative) = path.strip_prefix(base) {
-                out.push(relative.to_string_lossy().into_owned());
-            }
+/// Add the skills `skill` requires, and theirs, to `related`. `chain` is the
+/// path of requirements that led to `skill`.
+fn require_skills(
+    skill: &Skill,
+    available: &[SkillWithGroupContext],
+    chain: &mut Vec<String>,
+    seen: &mut std::collections::BTreeSet<String>,
+    related: &mut Vec<SkillWithGroupContext>,
+) {
+    for name in &skill.requires {
+        if chain.contains(name) {
+            tracing::warn!(
+                cycle = %format!("{} -> {name}", chain.join(" -> ")),
+                "skill requirement cycle; skipping"
+            );
+            continue;
+        }
+        if !seen.insert(name.clone()) {
+            continue;
         }
+        let Some(required) = available.iter().find(|entry| entry.skill.name() == name) else {
+            tracing::warn!(skill = %skill.name(), required = %name, "required skill not available");
+            continue;
+        };
+        related.push(required.clone());
+        chain.push(name.clone());
+        require_skills(&required.skill, available, chain, seen, related);
+        chain.pop();
     }
 }
 
-fn format_skill_entry(skill: &Skill, crate_names: &[String]) -> String {
-    let mut out = String::new();
-    let name = skill.name();
-    out.push_str(&format!("- **{name}**"));
-    if let Some(desc) = skill.frontmatter.get("description") {
-        out.push_str(&format!(": {desc}"));
-    }
-    out.push('\n');
-    if !crate_names.is_empty() {
-        out.push_str(&format!(
+/// Format the skills tagged for an error code or lint name.
+///
+/// Includes Rust-scoped skills and skills for crates in the workspace.
+/// Returns an empty string if no skill is tagged for `diagnostic`.
+pub async fn diagnostic_guidance_output(
+    diagnostic: &str,
+    registry: &PluginRegistry,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> String {
+    let tagged: Vec<_> = list(registry, workspace)
+        .await
+        .into_iter()
+        .filter(|entry| entry.skill.covers_diagnostic(diagnostic))
+        .filter(|entry| entry.is_for_workspace(workspace))
+        .collect();
+
+    if tagged.is_empty() {
+        String::new()
+    } else {
+        advice_from(tagged, Vec::new(), None, root, workspace)
+            .await
+            .format_output(&GuidanceOptions::configured())
+    }
+}
+
+/// The skills with `activation: triggers` that apply to the workspace.
+///
+/// Includes Rust-scoped skills and skills for crates in the workspace.
+pub async fn trigger_skills(
+    registry: &PluginRegistry,
+    workspace: &[(String, semver::Version)],
+) -> Vec<SkillWithGroupContext> {
+    list(registry, workspace)
+        .await
+        .into_iter()
+        .filter(|entry| entry.skill.activation == Activation::Triggers)
+        .filter(|entry| entry.is_for_workspace(workspace))
+        .collect()
+}
+
+/// Format the skills among `candidates` (see [`trigger_skills`]) whose
+/// `triggers` match `tool_use`, leaving out those whose SKILL.md is in
+/// `shown`.
+///
+/// Returns the guidance, empty if no skill is triggered, and the SKILL.md
+/// paths of the skills in it.
+pub async fn triggered_output(
+    tool_use: &ToolUse,
+    shown: &[PathBuf],
+    candidates: &[SkillWithGroupContext],
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> (String, Vec<PathBuf>) {
+    let mut triggered: Vec<_> = candidates
+        .iter()
+        .filter(|entry| {
+            !shown.contains(&entry.skill.path) && entry.skill.triggers.matches(tool_use)
+        })
+        .cloned()
+        .collect();
+    if triggered.is_empty() {
+        return (String::new(), Vec::new());
+    }
+
+    // A triggered skill is inlined, like an always-on one.
+    for entry in &mut triggered {
+        entry.skill.activation = Activation::Always;
+    }
+    let paths = triggered
+        .iter()
+        .map(|entry| entry.skill.path.clone())
+        .collect();
+    let mut out = match &tool_use.file {
+        Some(file) => format!("Guidance for `{}` on {}\n", tool_use.tool, file.display()),
+        None => format!("Guidance for `{}`\n", tool_use.tool),
+    };
+    out.push_str(
+        &advice_from(triggered, Vec::new(), None, root, workspace)
+            .await
+            .format_output(&GuidanceOptions::configured()),
+    );
+    (out, paths)
+}
+
+/// Normalize an error code or lint name for comparison: error codes are
+/// uppercased (`E0277`); lint names are lowercased with `_` separators and
+/// any `clippy::` prefix removed (`needless_return`).
+pub fn normalize_diagnostic(diagnostic: &str) -> String {
+    let diagnostic = diagnostic.trim();
+    if is_error_code(diagnostic) {
+        return diagnostic.to_uppercase();
+    }
+    let lint = diagnostic.to_lowercase().replace('-', "_");
+    lint.strip_prefix("clippy::").unwrap_or(&lint).to_string()
+}
+
+/// Whether `s` looks like a compiler error code (`E` followed by four digits).
+pub fn is_error_code(s: &str) -> bool {
+    s.len() == 5 && s.starts_with(['E', 'e']) && s[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Split resolved skills into inlined and optional advice by activation.
+/// `related` skills are always inlined, as related guidance, and are not
+/// listed among the optional skills.
+///
+/// Inlined bodies of templated skills are rendered for `for_crate` (skills
+/// found through a related crate, for that crate; related skills, for no
+/// crate); the workspace details they need are loaded for `root` through
+/// the shared [`ServerCache`](crate::server_cache::ServerCache), only if a
+/// templated skill is inlined.
+async fn advice_from(
+    entries: Vec<SkillWithGroupContext>,
+    related: Vec<SkillWithGroupContext>,
+    for_crate: Option<&str>,
+    root: &Path,
+    workspace: &[(String, semver::Version)],
+) -> CrateAdvice {
+    let mut advice = CrateAdvice {
+        default_content: Vec::new(),
+        optional_skills: Vec::new(),
+        related_content: Vec::new(),
+        related_crates: BTreeMap::new(),
+    };
+    let templated = entries
+        .iter()
+        .filter(|entry| entry.skill.activation == Activation::Always)
+        .chain(&related)
+        .any(|entry| entry.skill.template);
+    let details = if templated {
+        crate::server_cache::ServerCache::shared()
+            .workspace_details(root)
+            .await
+    } else {
+        Default::default()
+    };
+    let content = |skill: &Skill, for_crate: Option<&str>| {
+        let body = if skill.template {
+            render_body(skill, for_crate, &details, workspace)
+        } else {
+            skill.body.clone()
+        };
+        (skill.name().to_string(), skill.path.clone(), body)
+    };
+
+    for entry in entries {
+        if let Some(related_crate) = &entry.related_crate {
+            advice
+                .related_crates
+                .insert(entry.skill.name().to_string(), related_crate.clone());
+        }
+        match entry.skill.activation {
+            Activation::Always => {
+                let for_crate = entry.related_crate.as_deref().or(for_crate);
+                advice
+                    .default_content
+                    .push(content(&entry.skill, for_crate));
+            }
+            // A skill inlined as related guidance is not listed again.
+            Activation::Optional | Activation::Triggers
+                if related
+                    .iter()
+                    .any(|required| required.skill.name() == entry.skill.name()) => {}
+            Activation::Optional | Activation::Triggers => {
+                advice.optional_skills.push(entry.skill);
+            }
+        }
+    }
+    for entry in related {
+        advice.related_content.push(content(&entry.skill, None));
+    }
+
+    advice
+}
+
+/// Environment for templated skill bodies. Besides the minijinja builtins it
+/// has a `matching` test for version requirements, as in
+/// `{% if crate.version is matching(">=2.0") %}`.
+fn template_env() -> minijinja::Environment<'static> {
+    let mut env = minijinja::Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.add_test("matching", |version: &str, req: &str| {
+        match (
+            semver::Version::parse(version),
+            semver::VersionReq::parse(req),
+        ) {
+            (Ok(version), Ok(req)) => req.matches(&version),
+            _ => false,
+        }
+    });
+    env
+}
+
+/// Render a templated skill body.
+///
+/// `crate` describes `for_crate` (its name, resolved version, and enabled
+/// features) and is absent outside crate guidance; `workspace` holds the
+/// edition and the resolved version of every dependency. A body that fails
+/// to render is used as written.
+fn render_body(
+    skill: &Skill,
+    for_crate: Option<&str>,
+    details: &WorkspaceDetails,
+    workspace: &[(String, semver::Version)],
+) -> String {
+    let dependencies: BTreeMap<&str, String> = workspace
+        .iter()
+        .map(|(name, version)| (name.as_str(), version.to_string()))
+        .collect();
+
+    let mut context = serde_json::Map::new();
+    if let Some(name) = for_crate {
+        context.insert(
+            "crate".to_string(),
+            serde_json::json!({
+                "name": name,
+                "version": dependencies.get(name),
+                "features": details.features.get(name).cloned().unwrap_or_default(),
+            }),
+        );
+    }
+    context.insert(
+        "workspace".to_string(),
+        serde_json::json!({
+            "edition": details.edition,
+            "dependencies": dependencies,
+        }),
+    );
+
+    match template_env().render_str(&skill.body, context) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!(skill = %skill.name(), error = %e, "failed to render skill template");
+            skill.body.clone()
+        }
+    }
+}
+
+/// Format a skill listing entry for display (shared by list and guidance output).
+/// List resource files in a skill directory, as paths relative to that directory.
+///
+/// Excludes `SKILL.md` itself, and symlinks, which could point outside the
+/// skill directory. Returns sorted paths for deterministic output.
+pub fn list_skill_resources(skill_dir: &Path) -> Vec<String> {
+    let mut resources = Vec::new();
+    collect_resources_recursive(skill_dir, skill_dir, &mut resources);
+    resources.sort();
+    resources
+}
+
+fn collect_resources_recursive(base: &Path, dir: &Path, out: &mut Vec<String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_resources_recursive(base, &path, out);
+        } else if file_type.is_file() && path.file_name().is_some_and(|f| f != "SKILL.md") {
+            if let Ok(relative) = path.strip_prefix(base) {
+                out.push(relative.to_string_lossy().into_owned());
+            }
+        }
+    }
+}
+
+fn format_skill_entry(skill: &Skill, crate_names: &[String]) -> String {
+    let mut out = String::new();
+    let name = skill.name();
+    out.push_str(&format!("- **{name}**"));
+    if let Some(desc) = skill.frontmatter.get("description") {
+        out.push_str(&format!(": {desc}"));
+    }
+    out.push('\n');
+    if !crate_names.is_empty() {
+        out.push_str(&format!(
             "  - Applies to crates: {}\n",
             crate_names.join(", ")
         ));
     }
+    if !skill.topics.is_empty() {
+        out.push_str(&format!("  - Topics: {}\n", skill.topics.join(", ")));
+    }
     for (key, value) in &skill.frontmatter {
-        if key != "name" && key != "description" && key != "activation" {
+        if !["name", "description", "activation", "scope", "topics"].contains(&key.as_str()) {
             out.push_str(&format!("  - {key}: {value}\n"));
         }
     }
@@ -355,9 +1371,15 @@ async fn load_skills_for_group(
     let Some(dir) = resolve_skill_dir(plugin_path, group).await else {
         return (group_crates.to_vec(), Vec::new());
     };
+    // Skills may include fragments from anywhere in the plugin directory,
+    // when their own directory is inside it.
+    let root = match plugin_path.parent() {
+        Some(plugin_dir) if dir.starts_with(plugin_dir) => plugin_dir,
+        _ => &dir,
+    };
 
     let mut skills = Vec::new();
-    for result in discover_skills(&dir, group) {
+    for result in discover_skills(&dir, root, group) {
         match result {
             Ok(skill) => skills.push(skill),
             Err(e) => {
@@ -373,7 +1395,13 @@ async fn load_skills_for_group(
 ///
 /// Recursively searches for `SKILL.md` files, then prunes nested candidates
 /// (if `A/SKILL.md` exists, `A/B/SKILL.md` is excluded — skills don't nest).
-pub(crate) fn discover_skills(skills_dir: &Path, group: &SkillGroup) -> Vec<Result<Skill>> {
+///
+/// Includes in the skills may not leave `root`.
+pub(crate) fn discover_skills(
+    skills_dir: &Path,
+    root: &Path,
+    group: &SkillGroup,
+) -> Vec<Result<Skill>> {
     if !skills_dir.is_dir() {
         return Vec::new();
     }
@@ -384,7 +1412,7 @@ pub(crate) fn discover_skills(skills_dir: &Path, group: &SkillGroup) -> Vec<Resu
 
     skill_files
         .into_iter()
-        .map(|skill_md| load_skill(&skill_md, group))
+        .map(|skill_md| load_skill(&skill_md, root, group))
         .collect()
 }
 
@@ -458,13 +1486,16 @@ async fn resolve_skill_dir(plugin_path: &Path, group: &SkillGroup) -> Option<Pat
 /// Standalone skills must be self-contained: all metadata (crates,
 /// applies-when, activation) comes from the SKILL.md frontmatter.
 /// Returns an error if `crates` is missing (standalone skills have
-/// no group to inherit from).
-pub fn load_standalone_skill(skill_md_path: &Path) -> Result<Skill> {
-    let skill = load_skill(skill_md_path, &SkillGroup::default())?;
-    if skill.crates.is_empty() {
+/// no group to inherit from), unless the skill is `scope: rust`.
+///
+/// `root` is the plugin source directory; includes may not leave it.
+pub fn load_standalone_skill(skill_md_path: &Path, root: &Path) -> Result<Skill> {
+    let skill = load_skill(skill_md_path, root, &SkillGroup::default())?;
+    if skill.crates.is_empty() && skill.scope == Scope::Crate {
         bail!(
             "standalone skill `{}` is missing `crates` in frontmatter \
-             (standalone skills have no plugin group to inherit from)",
+             (standalone skills have no plugin group to inherit from; \
+             use `scope: rust` for general Rust guidance)",
             skill.name()
         );
     }
@@ -482,37 +1513,101 @@ pub fn load_standalone_skill(skill_md_path: &Path) -> Result<Skill> {
 /// A skill should have `crates` at either the skill level or
 /// the group level (or both). If neither provides it, a warning is logged
 /// but loading succeeds (the skill simply won't match any crate query).
-fn load_skill(skill_md_path: &Path, group: &SkillGroup) -> Result<Skill> {
+///
+/// Include directives in the body are expanded (see [`expand_includes`]);
+/// included files must be inside `root`.
+fn load_skill(skill_md_path: &Path, root: &Path, group: &SkillGroup) -> Result<Skill> {
     let content = std::fs::read_to_string(skill_md_path)
         .with_context(|| format!("failed to read {}", skill_md_path.display()))?;
 
-    let fm = parse_frontmatter(&content)
+    let mut fm = parse_frontmatter(&content)
         .with_context(|| format!("failed to parse frontmatter in {}", skill_md_path.display()))?;
+    fm.body = expand_skill_includes(&fm.body, skill_md_path, root)?;
 
-    let mut frontmatter = fm.fields;
-
-    // Strip surrounding quotes from name if present (YAML scalars may be quoted)
-    if let Some(name) = frontmatter.get_mut("name") {
-        if let Some(unquoted) = name.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
-            *name = unquoted.to_string();
-        }
-    }
+    let frontmatter = fm.fields;
 
     let name = frontmatter
         .get("name")
         .context("SKILL.md frontmatter missing required `name` field")?;
 
-    // Parse skill-level crates predicates (comma-separated).
+    // Parse skill-level crates predicates.
     // This is independent of group-level — both layers are ANDed at match time.
-    let crates = if let Some(ref crates_str) = fm.crates {
-        predicate::parse_comma_separated(crates_str)?
-    } else {
-        Vec::new()
+    let crates = parse_crates_entries(&fm.crates)?;
+
+    // A skill is Rust-scoped if it says so, or if its group covers topics
+    // rather than crates.
+    let scope = match frontmatter.get("scope") {
+        Some(scope) => parse_scope(scope)?,
+        None if group.crates.is_none() && group.topics.is_some() => Scope::Rust,
+        None => Scope::Crate,
+    };
+    if scope == Scope::Rust && !crates.is_empty() {
+        bail!("skill `{name}` has `scope: rust` but also lists `crates`");
+    }
+    let topics = match frontmatter.get("topics") {
+        Some(topics) => topics
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        None => group.topics.clone().unwrap_or_default(),
     };
 
+    let diagnostics = frontmatter
+        .get("diagnostics")
+        .map(|list| {
+            list.split(',')
+                .map(normalize_diagnostic)
+                .filter(|d| !d.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let overrides: Vec<String> = frontmatter
+        .get("overrides")
+        .map(|list| {
+            list.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(bad) = overrides.iter().find(|o| {
+        o.split_once('/')
+            .is_none_or(|(plugin, skill)| plugin.is_empty() || skill.is_empty())
+    }) {
+        bail!("skill `{name}` has `overrides: {bad}`; expected `<plugin>/<skill>`");
+    }
+
+    let requires = frontmatter
+        .get("requires")
+        .map(|list| {
+            list.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let family = frontmatter
+        .get("family")
+        .map(|family| family.trim().to_string())
+        .filter(|family| !family.is_empty());
+
+    let template = match frontmatter.get("template").map(|t| t.trim()) {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(other) => bail!("skill `{name}` has `template: {other}`; expected `true` or `false`"),
+    };
+    if template {
+        template_env()
+            .template_from_str(&fm.body)
+            .with_context(|| format!("skill `{name}` has an invalid template body"))?;
+    }
+
     // Warn if no crates at either level — the skill won't match anything,
     // but we don't fail so a misconfigured plugin can't bring down the tool.
-    if crates.is_empty() && group.crates.is_none() {
+    if scope == Scope::Crate && crates.is_empty() && group.crates.is_none() {
         tracing::warn!(
             skill = %name,
             "skill has no `crates` in SKILL.md frontmatter or plugin [[skills]] group"
@@ -520,33 +1615,156 @@ fn load_skill(skill_md_path: &Path, group: &SkillGroup) -> Result<Skill> {
     }
 
     // Parse skill-level applies-when predicates.
-    let applies_when = if !fm.applies_when.is_empty() {
-        predicate::parse_predicates(&fm.applies_when)?
-    } else {
-        Vec::new()
-    };
+    let applies_when = predicate::parse_predicates(&fm.applies_when)?;
+
+    let mut triggers = Triggers::default();
+    for (kind, entries) in fm.triggers {
+        let entries = entries
+            .iter()
+            .map(|entry| entry.trim().to_string())
+            .filter(|entry| !entry.is_empty());
+        match kind.as_str() {
+            "files" => triggers.files.extend(entries),
+            "tools" => triggers.tools.extend(entries),
+            "patterns" => triggers.patterns.extend(entries),
+            other => bail!(
+                "skill `{name}` has unknown trigger `{other}`; expected `files`, `tools`, or `patterns`"
+            ),
+        }
+    }
+    file_globs(&triggers.files)
+        .with_context(|| format!("skill `{name}` has an invalid trigger"))?;
 
-    // Resolve activation: frontmatter overrides group-level
+    // Resolve activation: frontmatter overrides group-level, and a skill
+    // with triggers is activated by them unless it says otherwise.
     let activation = if let Some(act) = frontmatter.get("activation") {
         parse_activation(act)?
+    } else if !triggers.is_empty() {
+        Activation::Triggers
     } else {
         group.activation.clone().unwrap_or_default()
     };
+    match (&activation, triggers.is_empty()) {
+        (Activation::Triggers, true) => {
+            bail!("skill `{name}` has `activation: triggers` but no `triggers`")
+        }
+        (Activation::Always | Activation::Optional, false) => {
+            bail!("skill `{name}` has `triggers` but not `activation: triggers`")
+        }
+        _ => {}
+    }
 
     Ok(Skill {
         frontmatter,
         crates,
         applies_when,
         activation,
+        scope,
+        topics,
+        diagnostics,
+        overrides,
+        family,
+        template,
+        triggers,
+        requires,
         body: fm.body,
         path: skill_md_path.to_path_buf(),
     })
 }
 
+/// Expand the include directives in the body of the skill at `skill_md_path`.
+fn expand_skill_includes(body: &str, skill_md_path: &Path, root: &Path) -> Result<String> {
+    if !body.lines().any(|line| include_target(line).is_some()) {
+        return Ok(body.to_string());
+    }
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", root.display()))?;
+    let skill_md = skill_md_path
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", skill_md_path.display()))?;
+    let mut stack = vec![skill_md.clone()];
+    expand_includes(body, &skill_md, &root, &mut stack)
+}
+
+/// Replace each `<!-- include: path -->` line of `text` with the contents of
+/// `path`, resolved against the directory of `file`, expanding includes in
+/// the included file too. Lines inside fenced code blocks are left alone.
+///
+/// Fails if an included file is missing, resolves outside `root`, or is
+/// already being included (`stack` holds the files being expanded).
+fn expand_includes(
+    text: &str,
+    file: &Path,
+    root: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<String> {
+    let mut out = String::new();
+    let mut in_fence = false;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let target = match include_target(line) {
+            Some(target) if !in_fence => target,
+            _ => {
+                out.push_str(line);
+                continue;
+            }
+        };
+
+        let base = file.parent().unwrap_or(root);
+        let included = base
+            .join(target)
+            .canonicalize()
+            .with_context(|| format!("include `{target}` in {} not found", file.display()))?;
+        if !included.starts_with(root) {
+            bail!(
+                "include `{target}` in {} is outside {}",
+                file.display(),
+                root.display()
+            );
+        }
+        if stack.contains(&included) {
+            let chain: Vec<_> = stack
+                .iter()
+                .chain([&included])
+                .map(|p| p.strip_prefix(root).unwrap_or(p).display().to_string())
+                .collect();
+            bail!("include cycle: {}", chain.join(" -> "));
+        }
+
+        let content = std::fs::read_to_string(&included)
+            .with_context(|| format!("failed to read {}", included.display()))?;
+        stack.push(included.clone());
+        let expanded = expand_includes(&content, &included, root, stack)?;
+        stack.pop();
+
+        out.push_str(&expanded);
+        if line.ends_with('\n') && !expanded.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// The path named by an include directive line (`<!-- include: path -->`).
+fn include_target(line: &str) -> Option<&str> {
+    let target = line
+        .trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim()
+        .strip_prefix("include:")?
+        .trim();
+    (!target.is_empty()).then_some(target)
+}
+
 /// Filter skills by crate and workspace constraints, collecting matches with group context.
 fn collect_matching_skills(
     skills: &[Skill],
     group_crates: &[Predicate],
+    origin: &SkillOrigin,
     for_crate: Option<&str>,
     workspace: &[(String, semver::Version)],
     results: &mut Vec<SkillWithGroupContext>,
@@ -558,6 +1776,8 @@ fn collect_matching_skills(
         results.push(SkillWithGroupContext {
             skill: skill.clone(),
             group_crates: group_crates.to_vec(),
+            origin: origin.clone(),
+            related_crate: None,
         });
     }
 }
@@ -583,42 +1803,157 @@ fn atoms_all_match(atoms: &[Predicate], workspace: &[(String, semver::Version)])
 }
 
 /// Raw frontmatter fields extracted from a SKILL.md file.
-/// `crates` is comma-separated on a single line; `applies_when` can appear multiple times.
 struct RawFrontmatter {
+    /// Other fields as strings: lists are comma-separated and nested maps
+    /// are flattened to dotted keys (e.g. `metadata.author`).
     fields: BTreeMap<String, String>,
-    /// Raw `crates` value (comma-separated predicate string).
-    crates: Option<String>,
+    /// `crates` entries: list items, or the single comma-separated string.
+    crates: Vec<String>,
+    /// `applies-when` predicates: list items, or one per occurrence.
     applies_when: Vec<String>,
+    /// `triggers` entries by kind (`files`, `tools`, `patterns`): list
+    /// items, or the single string.
+    triggers: BTreeMap<String, Vec<String>>,
     body: String,
 }
 
 /// Parse SKILL.md content: extract `---`-fenced frontmatter and body.
+///
+/// The frontmatter is YAML. `crates` may be a list or a comma-separated
+/// string, and `applies-when` a list or a single predicate. Frontmatter that is not valid YAML (e.g. an
+/// unquoted value containing `: `, or a repeated `applies-when` key) is
+/// read line by line as `key: value` pairs instead.
 fn parse_frontmatter(content: &str) -> Result<RawFrontmatter> {
+    let (frontmatter_text, body) = split_frontmatter(content)?;
+
+    let mut raw = match serde_yaml_ng::from_str::<serde_yaml_ng::Value>(frontmatter_text) {
+        Ok(serde_yaml_ng::Value::Mapping(mapping)) => parse_yaml_frontmatter(mapping),
+        Ok(serde_yaml_ng::Value::Null) => parse_yaml_frontmatter(Default::default()),
+        Ok(_) => bail!("frontmatter must be a mapping of keys to values"),
+        Err(e) => {
+            tracing::debug!(error = %e, "frontmatter is not valid YAML; reading it line by line");
+            parse_line_frontmatter(frontmatter_text)
+        }
+    };
+    raw.body = body.to_string();
+    Ok(raw)
+}
+
+/// Split SKILL.md content into frontmatter text and body. Fences are lines
+/// consisting of `---` alone, so a horizontal rule in the body is left alone.
+fn split_frontmatter(content: &str) -> Result<(&str, &str)> {
     let trimmed = content.trim_start();
-    if !trimmed.starts_with("---") {
+    let mut lines = trimmed.split_inclusive('\n');
+    let opening = lines.next().unwrap_or("");
+    if opening.trim_end() != "---" {
         bail!("SKILL.md must start with --- frontmatter fence");
     }
 
-    let after_first_fence = &trimmed[3..];
-    let after_first_fence = after_first_fence
-        .strip_prefix('\n')
-        .unwrap_or(after_first_fence);
+    let mut offset = opening.len();
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Ok((
+                &trimmed[opening.len()..offset],
+                &trimmed[offset + line.len()..],
+            ));
+        }
+        offset += line.len();
+    }
+    bail!("no closing --- fence in frontmatter")
+}
+
+fn parse_yaml_frontmatter(mapping: serde_yaml_ng::Mapping) -> RawFrontmatter {
+    let mut raw = RawFrontmatter {
+        fields: BTreeMap::new(),
+        crates: Vec::new(),
+        applies_when: Vec::new(),
+        triggers: BTreeMap::new(),
+        body: String::new(),
+    };
+    for (key, value) in mapping {
+        let key = yaml_to_string(&key);
+        match key.as_str() {
+            "crates" => raw.crates = predicate_strings(&value),
+            "applies-when" => raw.applies_when = predicate_strings(&value),
+            "triggers" => {
+                if let serde_yaml_ng::Value::Mapping(kinds) = &value {
+                    for (kind, entries) in kinds {
+                        raw.triggers
+                            .insert(yaml_to_string(kind), predicate_strings(entries));
+                    }
+                }
+                // Also kept as fields, so listings show the triggers.
+                flatten_yaml_field(key, &value, &mut raw.fields);
+            }
+            _ => flatten_yaml_field(key, &value, &mut raw.fields),
+        }
+    }
+    raw
+}
+
+/// Insert a field, flattening nested maps into dotted keys.
+fn flatten_yaml_field(
+    key: String,
+    value: &serde_yaml_ng::Value,
+    out: &mut BTreeMap<String, String>,
+) {
+    match value {
+        serde_yaml_ng::Value::Mapping(mapping) => {
+            for (sub_key, sub_value) in mapping {
+                flatten_yaml_field(format!("{key}.{}", yaml_to_string(sub_key)), sub_value, out);
+            }
+        }
+        _ => {
+            out.insert(key, yaml_to_string(value));
+        }
+    }
+}
+
+/// Render a YAML value as a field string; lists become comma-separated.
+fn yaml_to_string(value: &serde_yaml_ng::Value) -> String {
+    use serde_yaml_ng::Value;
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        Value::Sequence(items) => items
+            .iter()
+            .map(yaml_to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Mapping(_) | Value::Tagged(_) => serde_json::to_string(value).unwrap_or_default(),
+    }
+}
 
-    let end = after_first_fence
-        .find("\n---")
-        .context("no closing --- fence in frontmatter")?;
+/// Predicate entries from a list or a single (comma-separated) string.
+fn predicate_strings(value: &serde_yaml_ng::Value) -> Vec<String> {
+    match value {
+        serde_yaml_ng::Value::Sequence(items) => items.iter().map(yaml_to_string).collect(),
+        serde_yaml_ng::Value::Null => Vec::new(),
+        _ => vec![yaml_to_string(value)],
+    }
+}
 
-    let frontmatter_text = &after_first_fence[..end];
-    let body_start = end + 4; // "\n---".len()
-    let body = after_first_fence
-        .get(body_start..)
-        .unwrap_or("")
-        .strip_prefix('\n')
-        .unwrap_or(after_first_fence.get(body_start..).unwrap_or(""));
+/// Parse `crates` entries, each of which may be comma-separated.
+fn parse_crates_entries(entries: &[String]) -> Result<Vec<Predicate>> {
+    let mut predicates = Vec::new();
+    for entry in entries {
+        predicates.extend(predicate::parse_comma_separated(entry)?);
+    }
+    Ok(predicates)
+}
 
-    let mut fields = BTreeMap::new();
-    let mut crates = None;
-    let mut applies_when = Vec::new();
+/// Read frontmatter that is not valid YAML as one `key: value` per line,
+/// splitting on the first `:`. `applies-when` may be repeated.
+fn parse_line_frontmatter(frontmatter_text: &str) -> RawFrontmatter {
+    let mut raw = RawFrontmatter {
+        fields: BTreeMap::new(),
+        crates: Vec::new(),
+        applies_when: Vec::new(),
+        triggers: BTreeMap::new(),
+        body: String::new(),
+    };
 
     for line in frontmatter_text.lines() {
         let line = line.trim();
@@ -627,30 +1962,43 @@ fn parse_frontmatter(content: &str) -> Result<RawFrontmatter> {
         }
         if let Some((key, value)) = line.split_once(':') {
             let key = key.trim();
-            let value = value.trim().to_string();
+            let value = unquote(value.trim());
             if key == "crates" {
-                crates = Some(value);
+                raw.crates = vec![value.to_string()];
             } else if key == "applies-when" {
-                applies_when.push(value);
+                raw.applies_when.push(value.to_string());
             } else {
-                fields.insert(key.to_string(), value);
+                raw.fields.insert(key.to_string(), value.to_string());
             }
         }
     }
+    raw
+}
 
-    Ok(RawFrontmatter {
-        fields,
-        crates,
-        applies_when,
-        body: body.to_string(),
-    })
+/// Strip one pair of matching surrounding quotes.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+        .unwrap_or(value)
+}
+
+fn parse_scope(s: &str) -> Result<Scope> {
+    match s.trim().to_lowercase().as_str() {
+        "crate" => Ok(Scope::Crate),
+        "rust" => Ok(Scope::Rust),
+        other => bail!("unknown scope: {other:?} (expected \"crate\" or \"rust\")"),
+    }
 }
 
 fn parse_activation(s: &str) -> Result<Activation> {
     match s.trim().to_lowercase().as_str() {
         "default" => Ok(Activation::Always),
         "optional" => Ok(Activation::Optional),
-        other => bail!("unknown activation mode: {other:?} (expected \"default\" or \"optional\")"),
+        "triggers" => Ok(Activation::Triggers),
+        other => bail!(
+            "unknown activation mode: {other:?} (expected \"default\", \"optional\", or \"triggers\")"
+        ),
     }
 }
 
@@ -660,6 +2008,15 @@ mod tests {
     use indoc::indoc;
     use std::fs;
 
+    /// Wrap a skill as a standalone skill from a user source.
+    fn standalone(skill: Skill) -> crate::plugins::StandaloneSkill {
+        crate::plugins::StandaloneSkill {
+            source: "test".to_string(),
+            precedence: Precedence::User,
+            skill,
+        }
+    }
+
     /// Parse a predicate string for use in test fixtures.
     fn pred(s: &str) -> Predicate {
         crate::predicate::parse_predicates(&[s.to_string()])
@@ -685,7 +2042,7 @@ mod tests {
         let fm = parse_frontmatter(content).unwrap();
         assert_eq!(fm.fields.get("name").unwrap(), "my-skill");
         assert_eq!(fm.fields.get("description").unwrap(), "A test skill");
-        assert_eq!(fm.crates.as_deref(), Some("serde"));
+        assert_eq!(fm.crates, ["serde"]);
         assert!(fm.body.contains("# Body content"));
         assert!(fm.body.contains("Some instructions here."));
     }
@@ -701,7 +2058,63 @@ mod tests {
             Body.
         "};
         let fm = parse_frontmatter(content).unwrap();
-        assert_eq!(fm.crates.as_deref(), Some("serde, serde_json>=1.0, toml"));
+        assert_eq!(fm.crates, ["serde, serde_json>=1.0, toml"]);
+    }
+
+    #[test]
+    fn parse_frontmatter_yaml() {
+        let content = indoc! {r#"
+            ---
+            name: "yaml-skill"
+            description: >
+              Guidance that spans
+              several lines: with a colon.
+            crates: [serde, tokio>=1.0]
+            applies-when:
+              - tokio
+            allowed-tools: [Bash, Read]
+            metadata:
+              author: Jane
+              version: "1.0"
+            ---
+
+            Intro.
+
+            ---
+
+            After a horizontal rule.
+        "#};
+        let fm = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.fields["name"], "yaml-skill");
+        assert_eq!(
+            fm.fields["description"],
+            "Guidance that spans several lines: with a colon."
+        );
+        assert_eq!(fm.crates, ["serde", "tokio>=1.0"]);
+        assert_eq!(fm.applies_when, ["tokio"]);
+        assert_eq!(fm.fields["allowed-tools"], "Bash, Read");
+        assert_eq!(fm.fields["metadata.author"], "Jane");
+        assert_eq!(fm.fields["metadata.version"], "1.0");
+        assert!(fm.body.contains("After a horizontal rule."));
+    }
+
+    #[test]
+    fn parse_frontmatter_falls_back_to_lines() {
+        // Not valid YAML: an unquoted `: ` in a value and a repeated key.
+        let content = indoc! {"
+            ---
+            name: 'legacy'
+            description: Use when: parsing
+            applies-when: tokio
+            applies-when: serde
+            ---
+            Body.
+        "};
+        let fm = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.fields["name"], "legacy");
+        assert_eq!(fm.fields["description"], "Use when: parsing");
+        assert_eq!(fm.applies_when, ["tokio", "serde"]);
+        assert_eq!(fm.body, "Body.\n");
     }
 
     #[test]
@@ -738,7 +2151,7 @@ mod tests {
         .unwrap();
 
         let defaults = SkillGroup::default();
-        let skill = load_skill(&skill_md, &defaults).unwrap();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
 
         assert_eq!(skill.frontmatter.get("name").unwrap(), "test-skill");
         assert_eq!(skill.crates.len(), 1);
@@ -765,7 +2178,7 @@ mod tests {
         .unwrap();
 
         let defaults = SkillGroup::default();
-        let skill = load_skill(&skill_md, &defaults).unwrap();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
         assert_eq!(skill.crates.len(), 2);
         assert!(skill.crates[0].references_crate("serde"));
         assert!(skill.crates[1].references_crate("tokio"));
@@ -793,7 +2206,7 @@ mod tests {
             activation: Some(Activation::Always),
             ..Default::default()
         };
-        let skill = load_skill(&skill_md, &defaults).unwrap();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
 
         // Skill has no crates in frontmatter, so it's empty at skill level.
         // The plugin default provides the crates scope.
@@ -824,7 +2237,7 @@ mod tests {
             activation: Some(Activation::Always),
             ..Default::default()
         };
-        let skill = load_skill(&skill_md, &defaults).unwrap();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
 
         // Skill-level crates specializes (ANDs with) plugin defaults
         assert_eq!(skill.crates.len(), 1);
@@ -852,7 +2265,7 @@ mod tests {
 
         let defaults = SkillGroup::default();
         // No longer an error — just a warning. The skill loads but won't match anything.
-        let skill = load_skill(&skill_md, &defaults).unwrap();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
         assert!(skill.crates.is_empty());
     }
 
@@ -878,7 +2291,7 @@ mod tests {
             crates: Some(vec![pred("serde")]),
             ..Default::default()
         };
-        let skill = load_skill(&skill_md, &defaults).unwrap();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
         assert!(skill.crates.is_empty()); // skill-level is empty
         assert_eq!(skill.frontmatter.get("name").unwrap(), "no-own-crates");
     }
@@ -897,278 +2310,952 @@ mod tests {
                 applies-when: serde_json>=1.0
                 ---
 
-                Body.
-            "},
-        )
-        .unwrap();
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let defaults = SkillGroup::default();
+        let skill = load_skill(&skill_md, tmp.path(), &defaults).unwrap();
+        assert_eq!(skill.applies_when.len(), 2);
+        assert!(skill.applies_when[0].references_crate("serde"));
+        assert!(skill.applies_when[1].references_crate("serde_json"));
+    }
+
+    // --- Standalone skills ---
+
+    #[test]
+    fn load_standalone_skill_self_contained() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("my-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: my-standalone
+                description: A standalone skill
+                crates: serde
+                activation: default
+                ---
+
+                Standalone body.
+            "},
+        )
+        .unwrap();
+
+        let skill = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap();
+        assert_eq!(skill.name(), "my-standalone");
+        assert!(skill.crates[0].references_crate("serde"));
+        assert_eq!(skill.activation, Activation::Always);
+        assert!(skill.body.contains("Standalone body."));
+    }
+
+    #[test]
+    fn validate_standalone_skill_bad_crates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("bad-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: bad
+                crates: >=not_valid!!
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let err = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("failed to parse predicate"),
+            "expected parse error, got: {err}"
+        );
+    }
+
+    #[test]
+    fn validate_standalone_skill_bad_applies_when() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("bad-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: bad
+                crates: serde
+                applies-when: >=garbage!!
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let err = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("failed to parse predicate"),
+            "expected parse error, got: {err}"
+        );
+    }
+
+    #[test]
+    fn validate_standalone_skill_bad_activation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("bad-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: bad
+                crates: serde
+                activation: bogus
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let err = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown activation mode"),
+            "expected activation error, got: {err}"
+        );
+    }
+
+    #[test]
+    fn validate_standalone_skill_missing_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("bad-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                crates: serde
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let err = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("missing required `name` field"),
+            "expected missing name error, got: {err}"
+        );
+    }
+
+    #[test]
+    fn standalone_skill_requires_crates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("no-crates");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: no-crates
+                description: Missing crates
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let err = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("missing `crates`"),
+            "expected crates error, got: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_includes_standalone_skills() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("my-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: standalone-serde
+                description: Standalone serde skill
+                crates: serde
+                activation: default
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let skill = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![standalone(skill)],
+        };
+
+        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
+        let results = list(&registry, &workspace).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].skill.name(), "standalone-serde");
+        // No group context for standalone skills
+        assert!(results[0].group_crates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn guidance_includes_standalone_skills() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("my-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: standalone-serde
+                description: Standalone serde skill
+                crates: serde
+                activation: default
+                ---
+
+                Use serde standalone.
+            "},
+        )
+        .unwrap();
+
+        let skill = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![standalone(skill)],
+        };
+
+        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
+        let advice = guidance("serde", None, &registry, tmp.path(), &workspace).await;
+        assert_eq!(advice.default_content.len(), 1);
+        assert_eq!(advice.default_content[0].0, "standalone-serde");
+        assert!(
+            advice.default_content[0]
+                .2
+                .contains("Use serde standalone.")
+        );
+    }
+
+    #[tokio::test]
+    async fn guidance_skips_standalone_skill_wrong_crate() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("my-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            indoc! {"
+                ---
+                name: tokio-skill
+                crates: tokio
+                activation: default
+                ---
+
+                Body.
+            "},
+        )
+        .unwrap();
+
+        let skill = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![standalone(skill)],
+        };
+
+        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
+        let advice = guidance("serde", None, &registry, tmp.path(), &workspace).await;
+        assert!(advice.is_empty());
+    }
+
+    // --- Precedence and overrides ---
+
+    fn write_serde_skill(
+        dir: &Path,
+        source: &str,
+        precedence: Precedence,
+        frontmatter: &str,
+    ) -> crate::plugins::StandaloneSkill {
+        let skill_dir = dir.join(source);
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            format!(
+                "---\n{frontmatter}\ncrates: serde\nactivation: default\n---\n\nFrom {source}.\n"
+            ),
+        )
+        .unwrap();
+        crate::plugins::StandaloneSkill {
+            source: source.to_string(),
+            precedence,
+            skill: load_standalone_skill(&skill_dir.join("SKILL.md"), dir).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn higher_precedence_skill_shadows_same_name() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![
+                write_serde_skill(
+                    tmp.path(),
+                    "builtin",
+                    Precedence::Builtin,
+                    "name: serde-tips",
+                ),
+                write_serde_skill(tmp.path(), "mine", Precedence::User, "name: serde-tips"),
+                write_serde_skill(tmp.path(), "org", Precedence::Org, "name: serde-tips"),
+            ],
+        };
+
+        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
+        let advice = guidance("serde", None, &registry, tmp.path(), &workspace).await;
+        assert_eq!(advice.default_content.len(), 1);
+        assert!(advice.default_content[0].2.contains("From mine."));
+
+        let listing = list_output(&registry, &workspace).await;
+        assert!(listing.contains("- **serde-tips** from `builtin`: shadowed by `mine/serde-tips`"));
+        assert!(listing.contains("- **serde-tips** from `org`: shadowed by `mine/serde-tips`"));
+    }
+
+    #[tokio::test]
+    async fn same_name_for_different_crates_does_not_shadow() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let tokio_dir = tmp.path().join("tokio-source");
+        fs::create_dir_all(&tokio_dir).unwrap();
+        fs::write(
+            tokio_dir.join("SKILL.md"),
+            "---\nname: getting-started\ncrates: tokio\nactivation: default\n---\n\nFrom tokio.\n",
+        )
+        .unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![
+                write_serde_skill(
+                    tmp.path(),
+                    "mine",
+                    Precedence::User,
+                    "name: getting-started",
+                ),
+                crate::plugins::StandaloneSkill {
+                    source: "builtin".to_string(),
+                    precedence: Precedence::Builtin,
+                    skill: load_standalone_skill(&tokio_dir.join("SKILL.md"), tmp.path()).unwrap(),
+                },
+            ],
+        };
+
+        let workspace = vec![
+            ("serde".to_string(), semver::Version::new(1, 0, 0)),
+            ("tokio".to_string(), semver::Version::new(1, 0, 0)),
+        ];
+        let advice = guidance("tokio", None, &registry, tmp.path(), &workspace).await;
+        assert_eq!(advice.default_content.len(), 1);
+        assert!(advice.default_content[0].2.contains("From tokio."));
+
+        let listed = list(&registry, &workspace).await;
+        assert_eq!(listed.len(), 2);
+        let listing = list_output(&registry, &workspace).await;
+        assert!(!listing.contains("shadowed"), "{listing}");
+    }
+
+    #[tokio::test]
+    async fn overrides_remove_named_skill() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![
+                write_serde_skill(tmp.path(), "org", Precedence::Org, "name: serde-basics"),
+                write_serde_skill(
+                    tmp.path(),
+                    "builtin",
+                    Precedence::Builtin,
+                    "name: serde-advanced\noverrides: org/serde-basics",
+                ),
+            ],
+        };
+
+        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
+        let advice = guidance("serde", None, &registry, tmp.path(), &workspace).await;
+        assert_eq!(advice.default_content.len(), 1);
+        assert_eq!(advice.default_content[0].0, "serde-advanced");
+
+        let listing = list_output(&registry, &workspace).await;
+        assert!(
+            listing
+                .contains("- **serde-basics** from `org`: overridden by `builtin/serde-advanced`")
+        );
+    }
+
+    #[tokio::test]
+    async fn family_keeps_most_specific_matching_variant() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let variant = |name: &str, crates: &str| {
+            let skill_dir = tmp.path().join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(
+                skill_dir.join("SKILL.md"),
+                format!(
+                    "---\nname: {name}\nfamily: tokio-runtime\ncrates: {crates}\n\
+                     activation: default\n---\n\nBody of {name}.\n"
+                ),
+            )
+            .unwrap();
+            standalone(load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap())
+        };
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![
+                variant("runtime-general", "tokio"),
+                variant("runtime-legacy", "tokio<1.0"),
+                variant("runtime-modern", "tokio>=1.0"),
+            ],
+        };
+
+        let workspace = vec![("tokio".to_string(), semver::Version::new(1, 38, 0))];
+        let advice = guidance("tokio", None, &registry, tmp.path(), &workspace).await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["runtime-modern"]);
+
+        let workspace = vec![("tokio".to_string(), semver::Version::new(0, 2, 0))];
+        let advice = guidance("tokio", None, &registry, tmp.path(), &workspace).await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["runtime-legacy"]);
+
+        // A family of the same name from another source is a separate family.
+        let mut other = variant("other-runtime", "tokio");
+        other.source = "other".to_string();
+        let mut registry = registry;
+        registry.standalone_skills.push(other);
+        let advice = guidance("tokio", None, &registry, tmp.path(), &workspace).await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["runtime-legacy", "other-runtime"]);
+
+        // A crate outside the workspace is matched at the requested version.
+        let advice = guidance(
+            "tokio",
+            Some(&semver::Version::new(0, 3, 0)),
+            &registry,
+            tmp.path(),
+            &[],
+        )
+        .await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["runtime-legacy", "other-runtime"]);
+
+        let advice = guidance(
+            "tokio",
+            Some(&semver::Version::new(1, 40, 0)),
+            &registry,
+            tmp.path(),
+            &workspace,
+        )
+        .await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["runtime-modern", "other-runtime"]);
+    }
+
+    #[tokio::test]
+    async fn requires_resolves_related_skills_transitively() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let skill = |name: &str, crates: &str, requires: &str| {
+            let skill_dir = tmp.path().join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(
+                skill_dir.join("SKILL.md"),
+                format!(
+                    "---\nname: {name}\ncrates: {crates}\nrequires: {requires}\n\
+                     activation: default\n---\n\nBody of {name}.\n"
+                ),
+            )
+            .unwrap();
+            standalone(load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap())
+        };
+        let mut registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![
+                skill(
+                    "axum-routing",
+                    "axum",
+                    "tower-middleware, http-basics, missing, axum-testing",
+                ),
+                skill("axum-extractors", "axum", "axum-routing"),
+                skill("tower-middleware", "tower", "http-basics, axum-routing"),
+                skill("http-basics", "http", "tower-middleware"),
+            ],
+        };
+        // An optional skill for the crate itself, required by an inlined one.
+        let mut testing = skill("axum-testing", "axum", "http-basics");
+        testing.skill.activation = Activation::Optional;
+        registry.standalone_skills.push(testing);
+
+        let workspace = vec![
+            ("axum".to_string(), semver::Version::new(0, 8, 0)),
+            ("http".to_string(), semver::Version::new(1, 0, 0)),
+            ("tower".to_string(), semver::Version::new(0, 5, 0)),
+        ];
+        let advice = guidance("axum", None, &registry, tmp.path(), &workspace).await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["axum-routing", "axum-extractors"]);
+        let related: Vec<_> = advice.related_content.iter().map(|c| &c.0).collect();
+        assert_eq!(related, ["tower-middleware", "http-basics", "axum-testing"]);
+        assert!(advice.optional_skills.is_empty());
+
+        let output = advice.format_output(&GuidanceOptions::default());
+        let (guidance, related) = output.split_once("## Related guidance").unwrap();
+        assert!(guidance.contains("Body of axum-extractors."));
+        assert!(related.contains("Body of tower-middleware."));
+        assert!(related.contains("Body of http-basics."));
+    }
+
+    #[tokio::test]
+    async fn guidance_follows_related_crates() {
+        use crate::plugins::{Plugin, PluginRegistry};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let skill = |name: &str, crates: &str, activation: &str| {
+            let skill_dir = tmp.path().join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(
+                skill_dir.join("SKILL.md"),
+                format!(
+                    "---\nname: {name}\ndescription: About {name}\ncrates: {crates}\n\
+                     activation: {activation}\n---\n\nBody of {name}.\n"
+                ),
+            )
+            .unwrap();
+            standalone(load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap())
+        };
+        let registry = PluginRegistry {
+            plugins: vec![ParsedPlugin {
+                path: tmp.path().join("serde.toml"),
+                plugin: Plugin {
+                    name: "serde-plugin".to_string(),
+                    installation: None,
+                    hooks: Vec::new(),
+                    skills: Vec::new(),
+                    commands: Vec::new(),
+                    mcp_servers: Vec::new(),
+                    related: BTreeMap::from([(
+                        "serde_json".to_string(),
+                        vec!["serde".to_string(), "tokio".to_string()],
+                    )]),
+                },
+                precedence: Precedence::default(),
+            }],
+            standalone_skills: vec![
+                skill("json-values", "serde_json", "default"),
+                skill("serde-derive", "serde", "default"),
+                skill("serde-both", "serde, serde_json", "default"),
+                skill("serde-attrs", "serde", "optional"),
+                skill("tokio-basics", "tokio", "default"),
+            ],
+        };
+
+        let workspace = vec![
+            ("serde".to_string(), semver::Version::new(1, 0, 0)),
+            ("serde_json".to_string(), semver::Version::new(1, 0, 0)),
+        ];
+        let advice = guidance("serde_json", None, &registry, tmp.path(), &workspace).await;
+        let names: Vec<_> = advice.default_content.iter().map(|c| &c.0).collect();
+        assert_eq!(names, ["json-values", "serde-both", "serde-derive"]);
+        assert_eq!(
+            advice.related_crates,
+            BTreeMap::from([
+                ("serde-derive".to_string(), "serde".to_string()),
+                ("serde-attrs".to_string(), "serde".to_string()),
+            ])
+        );
+
+        let output = advice.format_output(&GuidanceOptions::default());
+        assert!(output.contains("<skill_content name=\"serde-derive\" related-crate=\"serde\">"));
+        assert!(output.contains("<skill_content name=\"serde-both\">"));
+        assert!(output.contains("  - From related crate: serde\n"));
+
+        let advice = guidance("serde", None, &registry, tmp.path(), &workspace).await;
+        assert!(advice.related_crates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn triggers_activate_on_matching_tool_use() {
+        use crate::plugins::PluginRegistry;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let skill = |name: &str, frontmatter: &str| {
+            let skill_dir = tmp.path().join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(
+                skill_dir.join("SKILL.md"),
+                format!("---\nname: {name}\n{frontmatter}\n---\n\nBody of {name}.\n"),
+            )
+            .unwrap();
+            load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path())
+        };
+        let migrations = skill(
+            "sqlx-migrations",
+            "crates: sqlx\ntriggers:\n  files: [\"migrations/**/*.sql\"]",
+        )
+        .unwrap();
+        assert_eq!(migrations.activation, Activation::Triggers);
+        let runtime = skill(
+            "tokio-main",
+            "crates: tokio\nactivation: triggers\ntriggers:\n  tools: [Edit, Write]\n  \
+             files: \"**/*.rs\"\n  patterns: \"#[tokio::main]\"",
+        )
+        .unwrap();
+
+        let err = skill("bare", "crates: tokio\nactivation: triggers").unwrap_err();
+        assert!(err.to_string().contains("no `triggers`"), "{err}");
+        let err = skill(
+            "mixed",
+            "crates: tokio\nactivation: default\ntriggers:\n  tools: Edit",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("not `activation: triggers`"),
+            "{err}"
+        );
+        let err = skill("typo", "crates: tokio\ntriggers:\n  globs: build.rs").unwrap_err();
+        assert!(err.to_string().contains("unknown trigger `globs`"), "{err}");
+
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![standalone(migrations.clone()), standalone(runtime)],
+        };
+        let workspace = vec![
+            ("sqlx".to_string(), semver::Version::new(0, 8, 0)),
+            ("tokio".to_string(), semver::Version::new(1, 40, 0)),
+        ];
+        let candidates = trigger_skills(&registry, &workspace).await;
+        assert_eq!(candidates.len(), 2);
+        let triggered = async |tool: &str, file: &str, text: &str, shown: Vec<PathBuf>| {
+            let tool_use = ToolUse {
+                tool: tool.to_string(),
+                file: Some(PathBuf::from(file)),
+                text: text.to_string(),
+            };
+            triggered_output(&tool_use, &shown, &candidates, tmp.path(), &workspace).await
+        };
+
+        let (output, paths) = triggered("Read", "migrations/0001_init.sql", "", vec![]).await;
+        assert_eq!(paths, std::slice::from_ref(&migrations.path));
+        assert!(output.starts_with("Guidance for `Read` on migrations/0001_init.sql\n"));
+        assert!(output.contains("Body of sqlx-migrations."));
 
-        let defaults = SkillGroup::default();
-        let skill = load_skill(&skill_md, &defaults).unwrap();
-        assert_eq!(skill.applies_when.len(), 2);
-        assert!(skill.applies_when[0].references_crate("serde"));
-        assert!(skill.applies_when[1].references_crate("serde_json"));
+        let (_, paths) = triggered(
+            "Read",
+            "migrations/0001_init.sql",
+            "",
+            vec![migrations.path],
+        )
+        .await;
+        assert!(paths.is_empty());
+
+        let text = "#[tokio::main]\nasync fn main() {}\n";
+        let (output, _) = triggered("Edit", "src/main.rs", text, vec![]).await;
+        assert!(output.contains("Body of tokio-main."));
+        let (output, _) = triggered("Read", "src/main.rs", text, vec![]).await;
+        assert!(output.is_empty());
+        let (output, _) = triggered("Edit", "src/lib.rs", "fn f() {}\n", vec![]).await;
+        assert!(output.is_empty());
+
+        // Triggered skills are listed, not inlined, in crate guidance.
+        let advice = guidance("tokio", None, &registry, tmp.path(), &workspace).await;
+        assert!(advice.default_content.is_empty());
+        assert_eq!(advice.optional_skills.len(), 1);
     }
 
-    // --- Standalone skills ---
+    // --- Templated bodies ---
+
+    #[tokio::test]
+    async fn templated_body_renders_workspace_context() {
+        use crate::plugins::PluginRegistry;
 
-    #[test]
-    fn load_standalone_skill_self_contained() {
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("my-skill");
-        fs::create_dir_all(&skill_dir).unwrap();
         fs::write(
-            skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: my-standalone
-                description: A standalone skill
-                crates: serde
-                activation: default
-                ---
-
-                Standalone body.
-            "},
+            tmp.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
         )
         .unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/lib.rs"), "").unwrap();
 
-        let skill = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap();
-        assert_eq!(skill.name(), "my-standalone");
-        assert!(skill.crates[0].references_crate("serde"));
-        assert_eq!(skill.activation, Activation::Always);
-        assert!(skill.body.contains("Standalone body."));
-    }
-
-    #[test]
-    fn validate_standalone_skill_bad_crates() {
-        let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("bad-skill");
+        let skill_dir = tmp.path().join("skills/foo-tips");
         fs::create_dir_all(&skill_dir).unwrap();
         fs::write(
             skill_dir.join("SKILL.md"),
-            indoc! {"
+            indoc! {r#"
                 ---
-                name: bad
-                crates: >=not_valid!!
+                name: foo-tips
+                crates: foo
+                activation: default
+                template: true
                 ---
 
-                Body.
-            "},
+                You are on {{ crate.version }}{% if crate.version is matching(">=2.0") %}; `run` was renamed to `start` in 2.0{% endif %}.
+                Edition {{ workspace.edition }}, bar {{ workspace.dependencies.bar }}.
+            "#},
         )
         .unwrap();
+        let registry = PluginRegistry {
+            plugins: Vec::new(),
+            standalone_skills: vec![standalone(
+                load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap(),
+            )],
+        };
 
-        let err = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap_err();
-        assert!(
-            err.to_string().contains("failed to parse predicate"),
-            "expected parse error, got: {err}"
+        let workspace = vec![
+            ("foo".to_string(), semver::Version::new(2, 1, 0)),
+            ("bar".to_string(), semver::Version::new(0, 3, 0)),
+        ];
+        let advice = guidance("foo", None, &registry, tmp.path(), &workspace).await;
+        assert_eq!(
+            advice.default_content[0].2.trim(),
+            "You are on 2.1.0; `run` was renamed to `start` in 2.0.\nEdition 2021, bar 0.3.0."
+        );
+
+        // An explicitly requested version is rendered in place of the workspace's.
+        let advice = guidance(
+            "foo",
+            Some(&semver::Version::new(1, 4, 0)),
+            &registry,
+            tmp.path(),
+            &workspace,
+        )
+        .await;
+        assert_eq!(
+            advice.default_content[0].2.trim(),
+            "You are on 1.4.0.\nEdition 2021, bar 0.3.0."
         );
     }
 
     #[test]
-    fn validate_standalone_skill_bad_applies_when() {
+    fn template_body_must_parse() {
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("bad-skill");
-        fs::create_dir_all(&skill_dir).unwrap();
+        let skill_md = tmp.path().join("SKILL.md");
         fs::write(
-            skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: bad
-                crates: serde
-                applies-when: >=garbage!!
-                ---
-
-                Body.
-            "},
+            &skill_md,
+            "---\nname: a\ncrates: serde\ntemplate: true\n---\n\n{% if %}\n",
         )
         .unwrap();
-
-        let err = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap_err();
-        assert!(
-            err.to_string().contains("failed to parse predicate"),
-            "expected parse error, got: {err}"
-        );
+        let err = load_standalone_skill(&skill_md, tmp.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("invalid template body"));
     }
 
     #[test]
-    fn validate_standalone_skill_bad_activation() {
+    fn overrides_must_be_qualified() {
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("bad-skill");
-        fs::create_dir_all(&skill_dir).unwrap();
+        let skill_md = tmp.path().join("SKILL.md");
         fs::write(
-            skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: bad
-                crates: serde
-                activation: bogus
-                ---
-
-                Body.
-            "},
+            &skill_md,
+            "---\nname: a\ncrates: serde\noverrides: serde-basics\n---\n\nBody.\n",
         )
         .unwrap();
-
-        let err = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap_err();
-        assert!(
-            err.to_string().contains("unknown activation mode"),
-            "expected activation error, got: {err}"
-        );
+        let err = load_standalone_skill(&skill_md, tmp.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("serde-basics"));
     }
 
+    // --- Includes ---
+
     #[test]
-    fn validate_standalone_skill_missing_name() {
+    fn includes_expand_shared_fragments() {
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("bad-skill");
+        fs::create_dir_all(tmp.path().join("fragments")).unwrap();
+        fs::write(
+            tmp.path().join("fragments/errors.md"),
+            "Use `thiserror` in libraries.\n<!-- include: footer.md -->\n",
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("fragments/footer.md"),
+            "See the error guide.",
+        )
+        .unwrap();
+
+        let skill_dir = tmp.path().join("skills/serde-errors");
         fs::create_dir_all(&skill_dir).unwrap();
         fs::write(
             skill_dir.join("SKILL.md"),
             indoc! {"
                 ---
+                name: serde-errors
                 crates: serde
                 ---
 
-                Body.
+                Intro.
+                <!-- include: ../../fragments/errors.md -->
+                ```markdown
+                <!-- include: not-expanded.md -->
+                ```
             "},
         )
         .unwrap();
 
-        let err = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap_err();
-        assert!(
-            err.to_string().contains("missing required `name` field"),
-            "expected missing name error, got: {err}"
-        );
+        let skill = load_standalone_skill(&skill_dir.join("SKILL.md"), tmp.path()).unwrap();
+        expect_test::expect![[r#"
+
+            Intro.
+            Use `thiserror` in libraries.
+            See the error guide.
+            ```markdown
+            <!-- include: not-expanded.md -->
+            ```
+        "#]]
+        .assert_eq(&skill.body);
     }
 
     #[test]
-    fn standalone_skill_requires_crates() {
+    fn includes_are_confined_and_acyclic() {
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("no-crates");
+        let root = tmp.path().join("source");
+        let skill_dir = root.join("a");
         fs::create_dir_all(&skill_dir).unwrap();
-        fs::write(
-            skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: no-crates
-                description: Missing crates
-                ---
-
-                Body.
-            "},
-        )
-        .unwrap();
+        fs::write(tmp.path().join("secret.md"), "Outside.").unwrap();
+        fs::write(root.join("one.md"), "<!-- include: two.md -->\n").unwrap();
+        fs::write(root.join("two.md"), "<!-- include: one.md -->\n").unwrap();
+
+        let load = |include: &str| {
+            fs::write(
+                skill_dir.join("SKILL.md"),
+                format!("---\nname: a\ncrates: serde\n---\n\n<!-- include: {include} -->\n"),
+            )
+            .unwrap();
+            format!(
+                "{:#}",
+                load_standalone_skill(&skill_dir.join("SKILL.md"), &root).unwrap_err()
+            )
+        };
 
-        let err = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap_err();
+        assert!(load("../../secret.md").contains("is outside"));
         assert!(
-            err.to_string().contains("missing `crates`"),
-            "expected crates error, got: {err}"
+            load("../one.md").contains("include cycle: a/SKILL.md -> one.md -> two.md -> one.md")
         );
+        assert!(load("missing.md").contains("not found"));
     }
 
-    #[tokio::test]
-    async fn list_includes_standalone_skills() {
-        use crate::plugins::PluginRegistry;
+    // --- Rust-scoped skills ---
 
-        let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("my-skill");
+    fn write_rust_skill(dir: &Path, name: &str, topics: &str, activation: &str) -> Skill {
+        let skill_dir = dir.join(name);
         fs::create_dir_all(&skill_dir).unwrap();
         fs::write(
             skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: standalone-serde
-                description: Standalone serde skill
-                crates: serde
-                activation: default
-                ---
-
-                Body.
-            "},
+            format!(
+                "---\nname: {name}\ndescription: About {topics}\nscope: rust\n\
+                 topics: {topics}\nactivation: {activation}\n---\n\nBody of {name}.\n"
+            ),
         )
         .unwrap();
+        load_standalone_skill(&skill_dir.join("SKILL.md"), dir).unwrap()
+    }
 
-        let skill = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap();
-        let registry = PluginRegistry {
-            plugins: Vec::new(),
-            standalone_skills: vec![skill],
-        };
+    #[test]
+    fn rust_scope_from_frontmatter_or_group_topics() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill = write_rust_skill(tmp.path(), "errors", "error-handling, anyhow", "optional");
+        assert_eq!(skill.scope, Scope::Rust);
+        assert_eq!(skill.topics, vec!["error-handling", "anyhow"]);
+        assert!(!skill.advises_on("anyhow"));
 
-        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
-        let results = list(&registry, &workspace).await;
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].skill.name(), "standalone-serde");
-        // No group context for standalone skills
-        assert!(results[0].group_crates.is_empty());
+        let skill_md = tmp.path().join("SKILL.md");
+        fs::write(&skill_md, "---\nname: unsafe-review\n---\n\nBody.\n").unwrap();
+        let group = SkillGroup {
+            topics: Some(vec!["unsafe".to_string()]),
+            ..Default::default()
+        };
+        let skill = load_skill(&skill_md, tmp.path(), &group).unwrap();
+        assert_eq!(skill.scope, Scope::Rust);
+        assert!(skill.covers_topic("UNSAFE"));
     }
 
-    #[tokio::test]
-    async fn guidance_includes_standalone_skills() {
-        use crate::plugins::PluginRegistry;
-
+    #[test]
+    fn rust_scope_rejects_crates() {
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("my-skill");
-        fs::create_dir_all(&skill_dir).unwrap();
+        let skill_md = tmp.path().join("SKILL.md");
         fs::write(
-            skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: standalone-serde
-                description: Standalone serde skill
-                crates: serde
-                activation: default
-                ---
-
-                Use serde standalone.
-            "},
+            &skill_md,
+            "---\nname: mixed\nscope: rust\ncrates: serde\n---\n\nBody.\n",
         )
         .unwrap();
-
-        let skill = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap();
-        let registry = PluginRegistry {
-            plugins: Vec::new(),
-            standalone_skills: vec![skill],
-        };
-
-        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
-        let advice = guidance("serde", &registry, &workspace).await;
-        assert_eq!(advice.default_content.len(), 1);
-        assert_eq!(advice.default_content[0].0, "standalone-serde");
-        assert!(
-            advice.default_content[0]
-                .2
-                .contains("Use serde standalone.")
-        );
+        let err = load_standalone_skill(&skill_md, tmp.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("also lists `crates`"));
     }
 
     #[tokio::test]
-    async fn guidance_skips_standalone_skill_wrong_crate() {
+    async fn rust_skills_are_listed_separately_and_served_by_topic() {
         use crate::plugins::PluginRegistry;
 
         let tmp = tempfile::tempdir().unwrap();
-        let skill_dir = tmp.path().join("my-skill");
-        fs::create_dir_all(&skill_dir).unwrap();
-        fs::write(
-            skill_dir.join("SKILL.md"),
-            indoc! {"
-                ---
-                name: tokio-skill
-                crates: tokio
-                activation: default
-                ---
-
-                Body.
-            "},
-        )
-        .unwrap();
-
-        let skill = load_standalone_skill(&skill_dir.join("SKILL.md")).unwrap();
         let registry = PluginRegistry {
             plugins: Vec::new(),
-            standalone_skills: vec![skill],
+            standalone_skills: vec![
+                standalone(write_rust_skill(
+                    tmp.path(),
+                    "errors",
+                    "error-handling",
+                    "default",
+                )),
+                standalone(write_rust_skill(
+                    tmp.path(),
+                    "async-idioms",
+                    "async",
+                    "optional",
+                )),
+            ],
         };
+        let workspace = vec![("tokio".to_string(), semver::Version::new(1, 0, 0))];
 
-        let workspace = vec![("serde".to_string(), semver::Version::new(1, 0, 0))];
-        let advice = guidance("serde", &registry, &workspace).await;
-        assert!(advice.is_empty());
+        // Never offered as crate guidance.
+        assert!(
+            guidance("tokio", None, &registry, tmp.path(), &workspace)
+                .await
+                .is_empty()
+        );
+
+        let listing = list_output(&registry, &workspace).await;
+        assert!(listing.starts_with("No skills available for crates"));
+        assert!(listing.contains("General Rust skills"));
+        assert!(
+            listing.contains("- **errors**: About error-handling\n  - Topics: error-handling\n")
+        );
+
+        let output =
+            rust_guidance_output(Some("error-handling"), &registry, tmp.path(), &workspace).await;
+        assert!(output.contains("Body of errors."));
+        assert!(!output.contains("async-idioms"));
+
+        let output = rust_guidance_output(Some("macros"), &registry, tmp.path(), &workspace).await;
+        assert_eq!(
+            output,
+            "No general Rust guidance for topic `macros`.\nTopics: async, error-handling\n"
+        );
     }
 
     // --- Discovery ---
@@ -1196,7 +3283,7 @@ mod tests {
         .unwrap();
 
         let defaults = SkillGroup::default();
-        let skills = discover_skills(&plugin_dir.join("skills"), &defaults);
+        let skills = discover_skills(&plugin_dir.join("skills"), tmp.path(), &defaults);
 
         assert_eq!(skills.len(), 1);
         let skill = skills.into_iter().next().unwrap().unwrap();
@@ -1225,7 +3312,7 @@ mod tests {
         .unwrap();
 
         let defaults = SkillGroup::default();
-        let skills = discover_skills(root, &defaults);
+        let skills = discover_skills(root, tmp.path(), &defaults);
 
         assert_eq!(skills.len(), 1);
         let skill = skills.into_iter().next().unwrap().unwrap();
@@ -1286,7 +3373,7 @@ mod tests {
         .unwrap();
 
         let defaults = SkillGroup::default();
-        let skills = discover_skills(root, &defaults);
+        let skills = discover_skills(root, tmp.path(), &defaults);
 
         // Should find shallow + sibling, but NOT nested (pruned by shallow)
         let names: Vec<String> = skills
@@ -1303,7 +3390,7 @@ mod tests {
     fn discover_skills_no_skills_dir() {
         let tmp = tempfile::tempdir().unwrap();
         let defaults = SkillGroup::default();
-        let skills = discover_skills(tmp.path(), &defaults);
+        let skills = discover_skills(tmp.path(), tmp.path(), &defaults);
         assert!(skills.is_empty());
     }
 
@@ -1314,9 +3401,114 @@ mod tests {
         let advice = CrateAdvice {
             default_content: vec![],
             optional_skills: vec![],
+            related_content: vec![],
+            related_crates: BTreeMap::new(),
         };
         assert!(advice.is_empty());
-        assert_eq!(advice.format_output(), "");
+        assert_eq!(advice.format_output(&GuidanceOptions::default()), "");
+    }
+
+    #[test]
+    fn crate_advice_format_with_resources() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("serde-tips");
+        fs::create_dir_all(skill_dir.join("examples")).unwrap();
+        fs::write(
+            skill_dir.join("examples/derive.rs"),
+            "#[derive(Serialize)]\nstruct A;",
+        )
+        .unwrap();
+        fs::write(skill_dir.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 1]).unwrap();
+        fs::write(skill_dir.join("huge.md"), "x".repeat(20 * 1024)).unwrap();
+        let advice = CrateAdvice {
+            default_content: vec![(
+                "serde-tips".into(),
+                skill_dir.join("SKILL.md"),
+                "Derive it.".into(),
+            )],
+            optional_skills: vec![],
+            related_content: vec![],
+            related_crates: BTreeMap::new(),
+        };
+
+        let listed_only = advice.format_output(&GuidanceOptions::default());
+        assert!(listed_only.contains("<file>examples/derive.rs</file>"));
+        assert!(!listed_only.contains("<skill_resource "));
+
+        let output = advice.format_output(&GuidanceOptions {
+            budget: None,
+            with_resources: true,
+        });
+        assert!(output.contains(indoc! {r#"
+            <skill_resource path="examples/derive.rs">
+            #[derive(Serialize)]
+            struct A;
+            </skill_resource>
+
+            Resources not inlined: huge.md (too large), logo.png (binary)
+            </skill_content>
+        "#}));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skill_resources_skip_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outside = tmp.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("id_rsa"), "secret").unwrap();
+        let skill_dir = tmp.path().join("skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(skill_dir.join("notes.md"), "notes").unwrap();
+        std::os::unix::fs::symlink(outside.join("id_rsa"), skill_dir.join("key")).unwrap();
+        std::os::unix::fs::symlink(&outside, skill_dir.join("etc")).unwrap();
+
+        assert_eq!(list_skill_resources(&skill_dir), ["notes.md"]);
+    }
+
+    #[test]
+    fn crate_advice_format_within_budget() {
+        let tmp = tempfile::tempdir().unwrap();
+        let small = tmp.path().join("small");
+        fs::create_dir_all(small.join("resources")).unwrap();
+        for i in 0..25 {
+            fs::write(small.join(format!("resources/r{i:02}.md")), "").unwrap();
+        }
+        let advice = CrateAdvice {
+            default_content: vec![
+                (
+                    "small".into(),
+                    small.join("SKILL.md"),
+                    "Short advice.".into(),
+                ),
+                (
+                    "big".into(),
+                    tmp.path().join("big/SKILL.md"),
+                    "Long advice. ".repeat(400),
+                ),
+            ],
+            optional_skills: vec![],
+            related_content: vec![],
+            related_crates: BTreeMap::new(),
+        };
+
+        let unlimited = advice.format_output(&GuidanceOptions::default());
+        assert!(unlimited.contains("r24.md"));
+        assert!(unlimited.contains("Long advice."));
+
+        let output = advice.format_output(&GuidanceOptions {
+            budget: Some(400),
+            with_resources: false,
+        });
+        assert!(output.contains("Short advice."));
+        assert!(output.contains("<file>resources/r19.md</file>\n  (5 more files)\n"));
+        assert!(!output.contains("r20.md"));
+        assert!(!output.contains("Long advice."));
+        assert!(output.contains("Output is limited to about 400 tokens."));
+        assert!(output.contains(&format!(
+            "- **big**: {}\n",
+            tmp.path().join("big/SKILL.md").display()
+        )));
     }
 
     #[test]
@@ -1328,6 +3520,8 @@ mod tests {
                 "Use serde this way.".into(),
             )],
             optional_skills: vec![],
+            related_content: vec![],
+            related_crates: BTreeMap::new(),
         };
         expect_test::expect![[r#"
 
@@ -1341,7 +3535,7 @@ mod tests {
             Relative paths in this skill are relative to the skill directory.
             </skill_content>
         "#]]
-        .assert_eq(&advice.format_output());
+        .assert_eq(&advice.format_output(&GuidanceOptions::default()));
     }
 
     #[test]
@@ -1358,9 +3552,19 @@ mod tests {
                 crates: vec![],
                 applies_when: vec![],
                 activation: Activation::Optional,
+                scope: Scope::Crate,
+                topics: vec![],
+                diagnostics: vec![],
+                overrides: vec![],
+                family: None,
+                template: false,
+                triggers: Triggers::default(),
+                requires: vec![],
                 body: String::new(),
                 path: PathBuf::from("/path/to/SKILL.md"),
             }],
+            related_content: vec![],
+            related_crates: BTreeMap::new(),
         };
         expect_test::expect![[r#"
 
@@ -1371,6 +3575,6 @@ mod tests {
               - compatibility: Requires Python 3.14+
               - Path: /path/to/SKILL.md
         "#]]
-        .assert_eq(&advice.format_output());
+        .assert_eq(&advice.format_output(&GuidanceOptions::default()));
     }
 }